@@ -0,0 +1,191 @@
+//! A queryable index over the contents of each elf's rucksack.
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::Item;
+
+/// The number of elves in each group sharing a badge.
+pub const GROUP_SIZE: usize = 3;
+
+/// The contents of a single elf's rucksack, split by compartment.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Rucksack {
+    pub first_compartment: HashSet<Item>,
+    pub second_compartment: HashSet<Item>,
+}
+
+impl Rucksack {
+    /// Parse a rucksack from a line, where the items in the first compartment occupy the first
+    /// half of the line and the items in the second occupy the second half.
+    pub fn parse(all_contents: &str) -> Rucksack {
+        let split_point = all_contents.len() / 2;
+        let mut items = all_contents.chars().map(Item);
+        let contents = items.by_ref();
+
+        let first_compartment = HashSet::from_iter(contents.take(split_point));
+        let second_compartment = HashSet::from_iter(contents.take(split_point));
+        Rucksack { first_compartment, second_compartment }
+    }
+
+    /// The items which are present in both compartments.
+    pub fn overlap(&self) -> HashSet<Item> {
+        self.first_compartment.intersection(&self.second_compartment).copied().collect()
+    }
+
+    /// All of the items in the rucksack, regardless of compartment.
+    pub fn whole_bag(&self) -> HashSet<Item> {
+        self.first_compartment.union(&self.second_compartment).copied().collect()
+    }
+
+    /// Which compartment(s) the item is in, if any.
+    pub fn locate(&self, item: &Item) -> Option<Compartment> {
+        match (self.first_compartment.contains(item), self.second_compartment.contains(item)) {
+            (true, true) => Some(Compartment::Both),
+            (true, false) => Some(Compartment::First),
+            (false, true) => Some(Compartment::Second),
+            (false, false) => None,
+        }
+    }
+}
+
+/// Parse a newline-separated sequence of rucksacks.
+pub fn parse_rucksacks(data: &str) -> Vec<Rucksack> {
+    data.split('\n').filter(|line| !line.is_empty()).map(Rucksack::parse).collect()
+}
+
+/// The compartment(s) of a rucksack in which an item was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compartment {
+    First,
+    Second,
+    Both,
+}
+
+/// An elf carrying a given item, and where they are carrying it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Carrier {
+    /// The (zero-based) index of the elf in the input.
+    pub elf: usize,
+    pub compartment: Compartment,
+}
+
+/// A summary of a group of elves.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GroupSummary {
+    /// The (zero-based) index of the group in the input.
+    pub group: usize,
+    /// The indices of the elves in the group.
+    pub elves: Vec<usize>,
+    /// The items carried by every elf in the group.
+    pub shared_items: Vec<Item>,
+    /// The items which each elf in the group has packed into both compartments.
+    pub duplicates: Vec<Vec<Item>>,
+    /// The combined score of the duplicated items.
+    pub duplicate_score: u64,
+}
+
+impl GroupSummary {
+    /// The group's badge, if exactly one item is shared by every elf.
+    pub fn badge(&self) -> Option<Item> {
+        match self.shared_items[..] {
+            [badge] => Some(badge),
+            _ => None,
+        }
+    }
+}
+
+/// An index over the parsed rucksacks, recording which elves carry each item.
+pub struct Inventory {
+    rucksacks: Vec<Rucksack>,
+    carriers: HashMap<Item, Vec<Carrier>>,
+}
+
+impl Inventory {
+    /// Build the index from the parsed rucksacks.
+    pub fn new(rucksacks: Vec<Rucksack>) -> Inventory {
+        let mut carriers: HashMap<Item, Vec<Carrier>> = HashMap::new();
+        for (elf, rucksack) in rucksacks.iter().enumerate() {
+            let mut items: Vec<Item> = rucksack.whole_bag().into_iter().collect();
+            items.sort();
+
+            for item in items {
+                if let Some(compartment) = rucksack.locate(&item) {
+                    carriers.entry(item).or_default().push(Carrier { elf, compartment });
+                }
+            }
+        }
+        Inventory { rucksacks, carriers }
+    }
+
+    /// The rucksacks in the index.
+    pub fn rucksacks(&self) -> &[Rucksack] {
+        &self.rucksacks
+    }
+
+    /// The elves carrying the item, in input order, and the compartment(s) it is in.
+    pub fn carriers(&self, item: &Item) -> &[Carrier] {
+        self.carriers.get(item).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The items which appear in every rucksack, in sorted order.
+    pub fn common_items(&self) -> Vec<Item> {
+        let n_rucksacks = self.rucksacks.len();
+        if n_rucksacks == 0 {
+            return Vec::new();
+        }
+
+        let mut items: Vec<Item> = self
+            .carriers
+            .iter()
+            .filter(|(_, carriers)| carriers.len() == n_rucksacks)
+            .map(|(item, _)| *item)
+            .collect();
+        items.sort();
+        items
+    }
+
+    /// The item most often packed into both compartments, and the number of elves that did so.
+    /// Ties are broken in favour of the lowest item.
+    pub fn most_common_duplicate(&self) -> Option<(Item, usize)> {
+        let mut counts: BTreeMap<Item, usize> = BTreeMap::new();
+        for (item, carriers) in &self.carriers {
+            let n_duplicated =
+                carriers.iter().filter(|carrier| carrier.compartment == Compartment::Both).count();
+            if n_duplicated > 0 {
+                counts.insert(*item, n_duplicated);
+            }
+        }
+
+        counts.into_iter().rev().max_by_key(|(_, count)| *count)
+    }
+
+    /// Summarise each full group of elves.
+    pub fn group_summaries(&self) -> Vec<GroupSummary> {
+        self.rucksacks
+            .chunks_exact(GROUP_SIZE)
+            .enumerate()
+            .map(|(group, members)| {
+                let first_elf = group * GROUP_SIZE;
+                let elves = (first_elf..first_elf + GROUP_SIZE).collect();
+
+                let mut shared = members[0].whole_bag();
+                for member in &members[1..] {
+                    shared = &shared & &member.whole_bag();
+                }
+                let mut shared_items: Vec<Item> = shared.into_iter().collect();
+                shared_items.sort();
+
+                let duplicates: Vec<Vec<Item>> = members
+                    .iter()
+                    .map(|member| {
+                        let mut overlap: Vec<Item> = member.overlap().into_iter().collect();
+                        overlap.sort();
+                        overlap
+                    })
+                    .collect();
+                let duplicate_score = duplicates.iter().flatten().map(Item::score).sum();
+
+                GroupSummary { group, elves, shared_items, duplicates, duplicate_score }
+            })
+            .collect()
+    }
+}
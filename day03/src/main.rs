@@ -1,8 +1,13 @@
 //! Solution to the third advent of code problem.
 use std::collections::HashSet;
+use std::env;
 use std::fs::read_to_string;
 use std::path::Path;
 
+mod inventory;
+
+use inventory::{parse_rucksacks, Compartment, Inventory, GROUP_SIZE};
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 struct Item(char);
 
@@ -27,36 +32,19 @@ impl Item {
 fn load_compartment_overlaps_badges(
     data: String,
 ) -> Result<(Vec<HashSet<Item>>, Vec<Item>), &'static str> {
-    let mut overlaps: Vec<HashSet<Item>> = Vec::new();
+    let rucksacks = parse_rucksacks(&data);
+    let overlaps: Vec<HashSet<Item>> =
+        rucksacks.iter().map(|rucksack| rucksack.overlap()).collect();
     let mut badges: Vec<Item> = Vec::new();
-    let mut elf_group: Vec<HashSet<Item>> = Vec::with_capacity(3);
 
-    for all_contents in data.split('\n') {
-        if all_contents.is_empty() {
-            continue;
+    for elf_group in rucksacks.chunks_exact(GROUP_SIZE) {
+        let elf_overlap =
+            &(&elf_group[0].whole_bag() & &elf_group[1].whole_bag()) & &elf_group[2].whole_bag();
+        if elf_overlap.len() != 1 {
+            return Err("Expected elf overlap to contain only one item for each group");
         }
-
-        let split_point = all_contents.len() / 2;
-        let mut items = all_contents.chars().map(Item);
-        let contents = items.by_ref();
-
-        let first_compartment: HashSet<Item> = HashSet::from_iter(contents.take(split_point));
-        let second_compartment: HashSet<Item> = HashSet::from_iter(contents.take(split_point));
-        let whole_bag = first_compartment.union(&second_compartment).copied().collect();
-        let overlap = first_compartment.intersection(&second_compartment).copied().collect();
-
-        overlaps.push(overlap);
-        elf_group.push(whole_bag);
-
-        if elf_group.len() == 3 {
-            let elf_overlap = &(&elf_group[0] & &elf_group[1]) & &elf_group[2];
-            if elf_overlap.len() != 1 {
-                return Err("Expected elf overlap to contain only one item for each group");
-            }
-            if let Some(badge) = elf_overlap.into_iter().next() {
-                badges.push(badge);
-            }
-            elf_group.clear();
+        if let Some(badge) = elf_overlap.into_iter().next() {
+            badges.push(badge);
         }
     }
 
@@ -73,11 +61,90 @@ fn score_badges(badges: &[Item]) -> u64 {
     badges.iter().map(Item::score).sum()
 }
 
+/// Answer a query against the inventory index, returning the lines to print.
+///
+/// Supported queries are:
+///  - `carriers <item>`: which elves carry the item, and in which compartment.
+///  - `common`: which items appear in every rucksack.
+///  - `duplicate`: the item most often packed into both compartments.
+///  - `groups`: a summary of each group of elves.
+fn run_query(inventory: &Inventory, query: &[String]) -> Result<Vec<String>, &'static str> {
+    let mut lines: Vec<String> = Vec::new();
+
+    match query.iter().map(String::as_str).collect::<Vec<&str>>()[..] {
+        ["carriers", item] => {
+            let mut characters = item.chars();
+            let item = match (characters.next(), characters.next()) {
+                (Some(character), None) => Item(character),
+                _ => return Err("Expected a single character item"),
+            };
+
+            for carrier in inventory.carriers(&item) {
+                let compartment = match carrier.compartment {
+                    Compartment::First => "first compartment",
+                    Compartment::Second => "second compartment",
+                    Compartment::Both => "both compartments",
+                };
+                lines
+                    .push(format!("Elf {} carries {} in the {}", carrier.elf, item.0, compartment));
+            }
+            if lines.is_empty() {
+                lines.push(format!("No elves carry {}", item.0));
+            }
+        }
+        ["common"] => {
+            let common: String = inventory.common_items().iter().map(|item| item.0).collect();
+            lines.push(format!(
+                "Items in all {} rucksacks: {}",
+                inventory.rucksacks().len(),
+                if common.is_empty() { "none" } else { &common }
+            ));
+        }
+        ["duplicate"] => match inventory.most_common_duplicate() {
+            Some((item, count)) => lines.push(format!(
+                "The most commonly duplicated item is {}, in {} rucksacks",
+                item.0, count
+            )),
+            None => lines.push("No items are duplicated".to_string()),
+        },
+        ["groups"] => {
+            for summary in inventory.group_summaries() {
+                let badge = summary.badge().map_or('?', |badge| badge.0);
+                let duplicates: Vec<String> = summary
+                    .duplicates
+                    .iter()
+                    .map(|items| items.iter().map(|item| item.0).collect())
+                    .collect();
+                lines.push(format!(
+                    "Group {} (elves {:?}): badge {}, duplicates [{}], duplicate score {}",
+                    summary.group,
+                    summary.elves,
+                    badge,
+                    duplicates.join(", "),
+                    summary.duplicate_score
+                ));
+            }
+        }
+        _ => return Err("Expected one of 'carriers <item>', 'common', 'duplicate' or 'groups'"),
+    }
+
+    Ok(lines)
+}
+
 /// Run the advent of code solution.
 fn main() {
     let path = Path::new("./input_01.txt");
     let input_string = read_to_string(path).expect("Unable to read input file 'input_01.txt'");
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("query") {
+        let inventory = Inventory::new(parse_rucksacks(&input_string));
+        for line in run_query(&inventory, &args[1..]).expect("Unable to run query") {
+            println!("{}", line);
+        }
+        return;
+    }
+
     let (overlaps, badges) =
         load_compartment_overlaps_badges(input_string).expect("Unable to parse overlaps/badges");
     let overlap_score = score_overlaps(&overlaps);
@@ -99,4 +166,39 @@ mod test {
         assert_eq!(Item('A').score(), 27);
         assert_eq!(Item(' ').score(), 0);
     }
+
+    const EXAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+";
+
+    /// Test that the inventory reports where each item is carried.
+    #[test]
+    fn test_inventory_carriers() {
+        let inventory = Inventory::new(parse_rucksacks(EXAMPLE));
+        let carriers: Vec<(usize, Compartment)> = inventory
+            .carriers(&Item('p'))
+            .iter()
+            .map(|carrier| (carrier.elf, carrier.compartment))
+            .collect();
+        assert_eq!(carriers, vec![(0, Compartment::Both), (5, Compartment::Second)]);
+        assert!(inventory.carriers(&Item('x')).is_empty());
+    }
+
+    /// Test the whole-inventory and per-group queries.
+    #[test]
+    fn test_inventory_queries() {
+        let inventory = Inventory::new(parse_rucksacks(EXAMPLE));
+        assert_eq!(inventory.common_items(), vec![]);
+        assert_eq!(inventory.most_common_duplicate(), Some((Item('L'), 1)));
+
+        let summaries = inventory.group_summaries();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].badge(), Some(Item('r')));
+        assert_eq!(summaries[1].badge(), Some(Item('Z')));
+        assert_eq!(summaries[0].duplicate_score, 16 + 38 + 42);
+    }
 }
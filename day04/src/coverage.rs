@@ -0,0 +1,125 @@
+//! A report on how well the cleaning assignments cover the sections, and how much work they
+//! duplicate.
+use std::collections::BTreeMap;
+
+use crate::interval::{Bound, Interval};
+use crate::CleaningRange;
+
+/// Summary of the sections covered by a set of cleaning ranges.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// The disjoint ranges of sections covered by at least one elf, in ascending order.
+    pub covered: Vec<CleaningRange>,
+    /// The ranges of sections between the first and last covered section that no elf covers.
+    pub gaps: Vec<CleaningRange>,
+    /// The largest number of elves assigned to any one section.
+    pub max_elves: usize,
+    /// The ranges of sections which are covered by `max_elves` elves.
+    pub busiest: Vec<CleaningRange>,
+    /// The number of sections covered by each number of elves (for one or more elves).
+    pub histogram: BTreeMap<usize, u64>,
+}
+
+impl CoverageReport {
+    /// Build the report by sweeping over the endpoints of the cleaning ranges.
+    pub fn new<'a, I: IntoIterator<Item = &'a CleaningRange>>(ranges: I) -> CoverageReport {
+        let mut events: Vec<(usize, isize)> = Vec::new();
+        for range in ranges {
+            events.push((range.start(), 1));
+            if let Some(after_end) = range.end().successor() {
+                events.push((after_end, -1));
+            }
+        }
+        events.sort();
+
+        let covered = Interval::merge(
+            segments(&events).filter(|(_, n_elves)| *n_elves > 0).map(|(segment, _)| segment),
+        );
+        let gaps = covered
+            .windows(2)
+            .filter_map(|pair| Interval::new(pair[0].end() + 1, pair[1].start() - 1).ok())
+            .collect();
+
+        let mut histogram: BTreeMap<usize, u64> = BTreeMap::new();
+        for (segment, n_elves) in segments(&events).filter(|(_, n_elves)| *n_elves > 0) {
+            *histogram.entry(n_elves).or_default() += segment.length();
+        }
+
+        let max_elves = histogram.keys().next_back().copied().unwrap_or(0);
+        let busiest = Interval::merge(
+            segments(&events)
+                .filter(|(_, n_elves)| max_elves > 0 && *n_elves == max_elves)
+                .map(|(segment, _)| segment),
+        );
+
+        CoverageReport { covered, gaps, max_elves, busiest, histogram }
+    }
+
+    /// The number of distinct sections covered by at least one elf.
+    pub fn total_sections(&self) -> u64 {
+        self.covered.iter().map(Interval::length).sum()
+    }
+
+    /// The number of sections cleaned in total, counting each elf's work separately.
+    pub fn total_work(&self) -> u64 {
+        self.histogram.iter().map(|(n_elves, n_sections)| *n_elves as u64 * n_sections).sum()
+    }
+
+    /// The number of section cleanings which duplicate work done by another elf.
+    pub fn duplicated_work(&self) -> u64 {
+        self.total_work() - self.total_sections()
+    }
+}
+
+/// The ranges of sections between consecutive sorted endpoint events, along with the number of
+/// elves covering each range. Ranges ending at the last section have no closing event, so any
+/// elves still covering the last event's section cover every section from there on.
+fn segments(events: &[(usize, isize)]) -> impl Iterator<Item = (CleaningRange, usize)> + '_ {
+    let mut n_elves: isize = 0;
+    events.iter().enumerate().filter_map(move |(index, (position, delta))| {
+        n_elves += delta;
+        let segment_end = match events.get(index + 1) {
+            Some((next_position, _)) => next_position.checked_sub(1)?,
+            None if n_elves > 0 => usize::MAX,
+            None => return None,
+        };
+        let segment = Interval::new(*position, segment_end).ok()?;
+        Some((segment, n_elves as usize))
+    })
+}
+
+/// Tests for the coverage report.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Test the report for the puzzle's example assignments, plus one range beyond a gap.
+    #[test]
+    fn test_coverage_report() {
+        let example = format!("{}12-13\n", crate::EXAMPLE);
+        let assignments = crate::parse_assignments(&example).unwrap();
+        let report = CoverageReport::new(assignments.iter().map(|assignment| &assignment.range));
+
+        let interval = |start, end| Interval::new(start, end).unwrap();
+        assert_eq!(report.covered, vec![interval(2, 9), interval(12, 13)]);
+        assert_eq!(report.gaps, vec![interval(10, 11)]);
+        assert_eq!(report.total_sections(), 10);
+        assert_eq!(report.max_elves, 8);
+        assert_eq!(report.busiest, vec![interval(6, 6)]);
+        assert_eq!(report.total_work(), 44);
+        assert_eq!(report.duplicated_work(), 34);
+        assert_eq!(report.histogram.values().sum::<u64>(), 10);
+    }
+
+    /// Test that ranges ending at the last section are covered up to it.
+    #[test]
+    fn test_open_ended_coverage() {
+        let assignments = crate::parse_assignments("5-18446744073709551615,7-9\n").unwrap();
+        let report = CoverageReport::new(assignments.iter().map(|assignment| &assignment.range));
+
+        assert_eq!(report.covered, vec![Interval::new(5, usize::MAX).unwrap()]);
+        assert_eq!(report.total_sections(), usize::MAX as u64 - 4);
+        assert_eq!(report.busiest, vec![Interval::new(7, 9).unwrap()]);
+        assert_eq!(report.duplicated_work(), 3);
+    }
+}
@@ -0,0 +1,163 @@
+//! A graph of the assignments across the whole input, joining each pair which overlap.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Write;
+
+use crate::sweep::find_overlapping_indices;
+use crate::Assignment;
+
+/// A graph with a node for each assignment, and an edge between each pair which overlap.
+pub struct OverlapGraph<'a> {
+    pub nodes: &'a [Assignment],
+    /// The pairs of indices of assignments which overlap.
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl<'a> OverlapGraph<'a> {
+    /// Build the graph, finding the edges with a sweep line.
+    pub fn new(assignments: &'a [Assignment]) -> OverlapGraph<'a> {
+        let edges = find_overlapping_indices(assignments)
+            .into_iter()
+            .map(|(first, second, _)| (first, second))
+            .collect();
+        OverlapGraph { nodes: assignments, edges }
+    }
+
+    /// The connected components of the graph, as sorted lists of node indices. The components
+    /// are ordered by their lowest node index.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut parents: Vec<usize> = (0..self.nodes.len()).collect();
+        fn root(parents: &mut [usize], mut node: usize) -> usize {
+            while parents[node] != node {
+                parents[node] = parents[parents[node]];
+                node = parents[node];
+            }
+            node
+        }
+
+        for (first, second) in &self.edges {
+            let first_root = root(&mut parents, *first);
+            let second_root = root(&mut parents, *second);
+            if first_root != second_root {
+                parents[first_root.max(second_root)] = first_root.min(second_root);
+            }
+        }
+
+        let mut components: Vec<Vec<usize>> = Vec::new();
+        let mut component_of_root: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        for node in 0..self.nodes.len() {
+            let node_root = root(&mut parents, node);
+            match component_of_root[node_root] {
+                Some(component) => components[component].push(node),
+                None => {
+                    component_of_root[node_root] = Some(components.len());
+                    components.push(vec![node]);
+                }
+            }
+        }
+        components
+    }
+
+    /// The largest connected component, preferring the first where several are equally large.
+    pub fn largest_component(&self) -> Option<Vec<usize>> {
+        self.components().into_iter().rev().max_by_key(Vec::len)
+    }
+
+    /// Colour the nodes so that no two overlapping assignments share a colour, returning the
+    /// colour of each node. Each colour is a "shift" of assignments which can be worked without
+    /// duplicating any effort.
+    ///
+    /// Assignments are coloured greedily in order of their start, reusing the lowest colour
+    /// freed by an assignment that has already ended. For an interval graph this uses the
+    /// minimum number of colours, which is the most elves assigned to any one section.
+    pub fn colouring(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.nodes.len()).collect();
+        order.sort_by_key(|node| self.nodes[*node].range);
+
+        let mut colours: Vec<usize> = vec![0; self.nodes.len()];
+        let mut n_colours = 0;
+        let mut open: BinaryHeap<Reverse<(usize, usize)>> = BinaryHeap::new();
+        let mut free: BinaryHeap<Reverse<usize>> = BinaryHeap::new();
+
+        for node in order {
+            let range = &self.nodes[node].range;
+            while let Some(Reverse((end, colour))) = open.peek().copied() {
+                if end >= range.start() {
+                    break;
+                }
+                open.pop();
+                free.push(Reverse(colour));
+            }
+
+            let colour = match free.pop() {
+                Some(Reverse(colour)) => colour,
+                None => {
+                    n_colours += 1;
+                    n_colours - 1
+                }
+            };
+            colours[node] = colour;
+            open.push(Reverse((range.end(), colour)));
+        }
+        colours
+    }
+
+    /// The minimum number of colours needed so that no overlapping assignments share a colour.
+    pub fn n_colours(&self) -> usize {
+        self.colouring().into_iter().max().map_or(0, |colour| colour + 1)
+    }
+
+    /// Export the graph in the DOT format, labelling each node with its line, position and range.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph overlaps {\n");
+        for (node, assignment) in self.nodes.iter().enumerate() {
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}:{} {}\"];",
+                node, assignment.line, assignment.position, assignment.range
+            );
+        }
+        for (first, second) in &self.edges {
+            let _ = writeln!(dot, "    n{} -- n{};", first, second);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Tests for the overlap graph.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::coverage::CoverageReport;
+    use crate::parse_assignments;
+
+    /// Test the components and colouring of a small graph.
+    #[test]
+    fn test_overlap_graph() {
+        let assignments = parse_assignments("2-4,6-8\n3-5,9-9\n20-22\n5-7,1-1\n").unwrap();
+        let graph = OverlapGraph::new(&assignments);
+
+        assert_eq!(graph.components(), vec![vec![0, 1, 2, 5], vec![3], vec![4], vec![6]]);
+        assert_eq!(graph.largest_component(), Some(vec![0, 1, 2, 5]));
+
+        let report = CoverageReport::new(assignments.iter().map(|assignment| &assignment.range));
+        assert_eq!(graph.n_colours(), report.max_elves);
+
+        let colours = graph.colouring();
+        for (first, second) in &graph.edges {
+            assert_ne!(colours[*first], colours[*second]);
+        }
+    }
+
+    /// Test the DOT export.
+    #[test]
+    fn test_dot_export() {
+        let assignments = parse_assignments("2-4,3-5\n").unwrap();
+        let dot = OverlapGraph::new(&assignments).to_dot();
+        assert_eq!(
+            dot,
+            "graph overlaps {\n    n0 [label=\"1:0 2-4\"];\n    n1 [label=\"1:1 3-5\"];\n    n0 -- n1;\n}\n"
+        );
+    }
+}
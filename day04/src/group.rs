@@ -0,0 +1,94 @@
+//! Analysis of the group of cleaning ranges assigned together on one line.
+use crate::interval::Interval;
+use crate::CleaningRange;
+
+/// How the ranges in a group duplicate each other's work.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GroupReport {
+    /// The positions of the ranges which are fully covered by the union of the other ranges.
+    pub covered: Vec<usize>,
+    /// The pairs of positions of ranges which overlap, in ascending order.
+    pub overlapping: Vec<(usize, usize)>,
+    /// The number of section cleanings which duplicate work done by another elf in the group.
+    pub redundancy: u64,
+}
+
+impl GroupReport {
+    /// Build the report for the ranges in a group.
+    pub fn new(ranges: &[CleaningRange]) -> GroupReport {
+        let covered = (0..ranges.len())
+            .filter(|position| {
+                let others = ranges
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_position, _)| other_position != position)
+                    .map(|(_, other)| *other);
+                let range = &ranges[*position];
+                Interval::merge(others).iter().any(|union| union.contains(range))
+            })
+            .collect();
+
+        let mut overlapping: Vec<(usize, usize)> = Vec::new();
+        for (position, range) in ranges.iter().enumerate() {
+            for (other_position, other) in ranges.iter().enumerate().skip(position + 1) {
+                if range.overlaps(other) {
+                    overlapping.push((position, other_position));
+                }
+            }
+        }
+
+        let total_work: u64 = ranges.iter().map(Interval::length).sum();
+        let distinct: u64 =
+            Interval::merge(ranges.iter().copied()).iter().map(Interval::length).sum();
+
+        GroupReport { covered, overlapping, redundancy: total_work - distinct }
+    }
+
+    /// Whether any range in the group completely replicates the work of the others. For a pair
+    /// of ranges, this is whether either contains the other.
+    pub fn replicates(&self) -> bool {
+        !self.covered.is_empty()
+    }
+
+    /// Whether any range in the group replicates some work of another.
+    pub fn overlaps(&self) -> bool {
+        !self.overlapping.is_empty()
+    }
+}
+
+/// Tests for the group analysis.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_range_groups, EXAMPLE};
+
+    /// Test that pairs of ranges give the puzzle's answers.
+    #[test]
+    fn test_pair_groups() {
+        let reports: Vec<GroupReport> = parse_range_groups(EXAMPLE)
+            .unwrap()
+            .iter()
+            .map(|group| GroupReport::new(group))
+            .collect();
+
+        assert_eq!(reports.iter().filter(|report| report.replicates()).count(), 2);
+        assert_eq!(reports.iter().filter(|report| report.overlaps()).count(), 4);
+        assert_eq!(reports[3].covered, vec![1]);
+        assert_eq!(reports[4].covered, vec![0]);
+    }
+
+    /// Test a group where one range is covered by two others together, but by neither alone.
+    #[test]
+    fn test_three_way_group() {
+        let groups = parse_range_groups("1-5,4-9,6-12\n3-3\n").unwrap();
+
+        let report = GroupReport::new(&groups[0]);
+        assert_eq!(report.covered, vec![1]);
+        assert_eq!(report.overlapping, vec![(0, 1), (1, 2)]);
+        assert_eq!(report.redundancy, 6);
+
+        let report = GroupReport::new(&groups[1]);
+        assert!(!report.replicates() && !report.overlaps());
+        assert_eq!(report.redundancy, 0);
+    }
+}
@@ -0,0 +1,224 @@
+//! An index over the assignments across the whole input, answering point and range queries.
+use std::cmp::Reverse;
+
+use crate::interval::{Bound, Interval};
+use crate::{Assignment, CleaningRange};
+
+/// A node in a centred interval tree, holding the assignments which include its centre.
+struct Node {
+    centre: usize,
+    /// Indices of the assignments including the centre, in ascending order of start.
+    by_start: Vec<usize>,
+    /// Indices of the assignments including the centre, in descending order of end.
+    by_end: Vec<usize>,
+    /// The subtree of assignments ending before the centre.
+    left: Option<Box<Node>>,
+    /// The subtree of assignments starting after the centre.
+    right: Option<Box<Node>>,
+}
+
+impl Node {
+    /// Build a subtree from the indices of some assignments, returning `None` if there are none.
+    fn build(assignments: &[Assignment], indices: Vec<usize>) -> Option<Box<Node>> {
+        if indices.is_empty() {
+            return None;
+        }
+
+        let mut endpoints: Vec<usize> = indices
+            .iter()
+            .flat_map(|index| [assignments[*index].range.start(), assignments[*index].range.end()])
+            .collect();
+        let middle = endpoints.len() / 2;
+        let centre = *endpoints.select_nth_unstable(middle).1;
+
+        let (mut left, mut right, mut here) = (Vec::new(), Vec::new(), Vec::new());
+        for index in indices {
+            let range = &assignments[index].range;
+            if range.end() < centre {
+                left.push(index);
+            } else if range.start() > centre {
+                right.push(index);
+            } else {
+                here.push(index);
+            }
+        }
+
+        let mut by_start = here.clone();
+        by_start.sort_by_key(|index| assignments[*index].range.start());
+        let mut by_end = here;
+        by_end.sort_by_key(|index| Reverse(assignments[*index].range.end()));
+
+        Some(Box::new(Node {
+            centre,
+            by_start,
+            by_end,
+            left: Node::build(assignments, left),
+            right: Node::build(assignments, right),
+        }))
+    }
+
+    /// Collect the indices of the assignments which overlap the query range.
+    fn query(&self, assignments: &[Assignment], query: &CleaningRange, found: &mut Vec<usize>) {
+        if query.end() < self.centre {
+            let starts_in_range = self
+                .by_start
+                .iter()
+                .take_while(|index| assignments[**index].range.start() <= query.end());
+            found.extend(starts_in_range);
+        } else if query.start() > self.centre {
+            let ends_in_range = self
+                .by_end
+                .iter()
+                .take_while(|index| assignments[**index].range.end() >= query.start());
+            found.extend(ends_in_range);
+        } else {
+            found.extend(&self.by_start);
+        }
+
+        if query.start() < self.centre {
+            if let Some(left) = &self.left {
+                left.query(assignments, query, found);
+            }
+        }
+        if query.end() > self.centre {
+            if let Some(right) = &self.right {
+                right.query(assignments, query, found);
+            }
+        }
+    }
+}
+
+/// An index over the assignments, built once and then queried in O(log n + k) time for n
+/// assignments and k results.
+pub struct AssignmentIndex<'a> {
+    assignments: &'a [Assignment],
+    tree: Option<Box<Node>>,
+    /// The sections at which the number of elves changes, and the number of elves from there.
+    steps: Vec<(usize, usize)>,
+}
+
+impl<'a> AssignmentIndex<'a> {
+    /// Build the index over the assignments.
+    pub fn new(assignments: &'a [Assignment]) -> AssignmentIndex<'a> {
+        let tree = Node::build(assignments, (0..assignments.len()).collect());
+
+        let mut events: Vec<(usize, isize)> = Vec::new();
+        for assignment in assignments {
+            events.push((assignment.range.start(), 1));
+            if let Some(after_end) = assignment.range.end().successor() {
+                events.push((after_end, -1));
+            }
+        }
+        events.sort();
+
+        let mut steps: Vec<(usize, usize)> = Vec::new();
+        let mut n_elves: isize = 0;
+        for (position, delta) in events {
+            n_elves += delta;
+            match steps.last_mut() {
+                Some((last_position, last_n_elves)) if *last_position == position => {
+                    *last_n_elves = n_elves as usize;
+                }
+                _ => steps.push((position, n_elves as usize)),
+            }
+        }
+
+        AssignmentIndex { assignments, tree, steps }
+    }
+
+    /// The assignments which include the section, in no particular order.
+    pub fn cleaning(&self, section: usize) -> Vec<&'a Assignment> {
+        self.intersecting(&Interval::point(section))
+    }
+
+    /// The assignments which share any sections with the range, in no particular order.
+    pub fn intersecting(&self, range: &CleaningRange) -> Vec<&'a Assignment> {
+        let mut found: Vec<usize> = Vec::new();
+        if let Some(tree) = &self.tree {
+            tree.query(self.assignments, range, &mut found);
+        }
+        found.into_iter().map(|index| &self.assignments[index]).collect()
+    }
+
+    /// Split the range into segments of sections cleaned by the same number of elves, returning
+    /// each segment with its number of elves in ascending order of section.
+    pub fn coverage(&self, range: &CleaningRange) -> Vec<(CleaningRange, usize)> {
+        let first_step = self.steps.partition_point(|(position, _)| *position <= range.start());
+        let mut n_elves = first_step.checked_sub(1).map_or(0, |step| self.steps[step].1);
+        let mut segment_start = range.start();
+
+        let mut segments: Vec<(CleaningRange, usize)> = Vec::new();
+        for (position, step_n_elves) in
+            self.steps[first_step..].iter().take_while(|(position, _)| *position <= range.end())
+        {
+            if *step_n_elves == n_elves {
+                continue;
+            }
+            if let Ok(segment) = Interval::new(segment_start, position - 1) {
+                segments.push((segment, n_elves));
+            }
+            segment_start = *position;
+            n_elves = *step_n_elves;
+        }
+        if let Ok(segment) = Interval::new(segment_start, range.end()) {
+            segments.push((segment, n_elves));
+        }
+        segments
+    }
+}
+
+/// Tests for the assignment index.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_assignments, EXAMPLE};
+
+    /// Test that the index gives the same answers as checking every assignment.
+    #[test]
+    fn test_index_matches_brute_force() {
+        let example = format!("{}12-13,0-0\n", EXAMPLE);
+        let assignments = parse_assignments(&example).unwrap();
+        let index = AssignmentIndex::new(&assignments);
+
+        for start in 0..15 {
+            for end in start..15 {
+                let query = Interval::new(start, end).unwrap();
+                let expected: Vec<&Assignment> = assignments
+                    .iter()
+                    .filter(|assignment| assignment.range.overlaps(&query))
+                    .collect();
+                let mut found = index.intersecting(&query);
+                found.sort_by_key(|assignment| (assignment.line, assignment.position));
+                assert_eq!(found, expected);
+
+                let expected_coverage: Vec<usize> =
+                    (start..=end).map(|section| index.cleaning(section).len()).collect();
+                let coverage = index.coverage(&query);
+                let found_coverage: Vec<usize> = coverage
+                    .iter()
+                    .flat_map(|(segment, n_elves)| {
+                        (segment.start()..=segment.end()).map(|_| *n_elves)
+                    })
+                    .collect();
+                assert_eq!(found_coverage, expected_coverage);
+                assert!(coverage.windows(2).all(|pair| pair[0].1 != pair[1].1));
+            }
+        }
+
+        let mut cleaning_six: Vec<(usize, usize)> = index
+            .cleaning(6)
+            .iter()
+            .map(|assignment| (assignment.line, assignment.position))
+            .collect();
+        cleaning_six.sort();
+        assert_eq!(
+            cleaning_six,
+            vec![(1, 1), (3, 0), (4, 0), (4, 1), (5, 0), (5, 1), (6, 0), (6, 1)]
+        );
+
+        let everything = Interval::new(1, usize::MAX).unwrap();
+        let coverage = index.coverage(&everything);
+        assert_eq!(coverage.first(), Some(&(Interval::new(1, 1).unwrap(), 0)));
+        assert_eq!(coverage.last(), Some(&(Interval::new(14, usize::MAX).unwrap(), 0)));
+    }
+}
@@ -0,0 +1,276 @@
+//! Closed intervals over integer bounds, and the set algebra between them.
+use std::cmp::{max, min};
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+
+/// An integer type which can bound an interval.
+pub trait Bound: Copy + Ord + fmt::Debug + fmt::Display {
+    /// The next value up, if it exists.
+    fn successor(self) -> Option<Self>;
+    /// The next value down, if it exists.
+    fn predecessor(self) -> Option<Self>;
+    /// The distance from this value up to another (larger or equal) value.
+    fn distance_to(self, other: Self) -> u64;
+}
+
+macro_rules! impl_bound {
+    ($($bound_type:ty),*) => {
+        $(
+            impl Bound for $bound_type {
+                fn successor(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn predecessor(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn distance_to(self, other: Self) -> u64 {
+                    (other as i128 - self as i128) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_bound!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// The reason an interval could not be created.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntervalError {
+    /// The interval would contain no values (e.g. the half-open range `3..3`).
+    Empty,
+    /// The start of the interval is after its end.
+    Inverted,
+}
+
+impl fmt::Display for IntervalError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntervalError::Empty => write!(formatter, "interval contains no values"),
+            IntervalError::Inverted => write!(formatter, "interval start is after its end"),
+        }
+    }
+}
+
+impl std::error::Error for IntervalError {}
+
+/// A non-empty closed interval, containing both its start and its end.
+///
+/// Intervals are ordered by their start, then by their end.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Interval<T: Bound> {
+    start: T,
+    end: T,
+}
+
+/// The union of two intervals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Union<T: Bound> {
+    /// The intervals overlap or touch, so their union is a single interval.
+    Merged(Interval<T>),
+    /// The intervals are separated by a gap, in ascending order.
+    Disjoint(Interval<T>, Interval<T>),
+}
+
+impl<T: Bound> Interval<T> {
+    /// Create the interval from `start` to `end` inclusive.
+    pub fn new(start: T, end: T) -> Result<Interval<T>, IntervalError> {
+        if start > end {
+            return Err(IntervalError::Inverted);
+        }
+        Ok(Interval { start, end })
+    }
+
+    /// Create an interval containing only a single value.
+    pub fn point(value: T) -> Interval<T> {
+        Interval { start: value, end: value }
+    }
+
+    /// The first value in the interval.
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    /// The last value in the interval.
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    /// The number of values in the interval (saturating at `u64::MAX`).
+    pub fn length(&self) -> u64 {
+        self.start.distance_to(self.end).saturating_add(1)
+    }
+
+    /// Whether the interval includes the value.
+    pub fn includes(&self, value: T) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    /// Whether the interval duplicates _any_ values in the other interval.
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.start <= other.end && self.end >= other.start
+    }
+
+    /// Whether the interval fully contains the other.
+    pub fn contains(&self, other: &Interval<T>) -> bool {
+        other.start >= self.start && other.end <= self.end
+    }
+
+    /// Whether either interval fully contains the other.
+    pub fn replicates(&self, other: &Interval<T>) -> bool {
+        self.contains(other) || other.contains(self)
+    }
+
+    /// Whether the intervals do not overlap, but have no gap between them.
+    pub fn adjacent(&self, other: &Interval<T>) -> bool {
+        self.end.successor() == Some(other.start) || other.end.successor() == Some(self.start)
+    }
+
+    /// The values in both intervals, if any.
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        Interval::new(max(self.start, other.start), min(self.end, other.end)).ok()
+    }
+
+    /// The values in either interval.
+    pub fn union(&self, other: &Interval<T>) -> Union<T> {
+        if self.overlaps(other) || self.adjacent(other) {
+            let start = min(self.start, other.start);
+            let end = max(self.end, other.end);
+            return Union::Merged(Interval { start, end });
+        }
+
+        if self < other {
+            Union::Disjoint(*self, *other)
+        } else {
+            Union::Disjoint(*other, *self)
+        }
+    }
+
+    /// The values in this interval which are not in the other, as zero, one or two intervals in
+    /// ascending order.
+    pub fn difference(&self, other: &Interval<T>) -> Vec<Interval<T>> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+
+        let mut remainder = Vec::with_capacity(2);
+        if let Some(before_end) = other.start.predecessor() {
+            if let Ok(before) = Interval::new(self.start, before_end) {
+                remainder.push(before);
+            }
+        }
+        if let Some(after_start) = other.end.successor() {
+            if let Ok(after) = Interval::new(after_start, self.end) {
+                remainder.push(after);
+            }
+        }
+        remainder
+    }
+
+    /// Merge a collection of intervals into the smallest ascending sequence of disjoint intervals
+    /// which covers the same values. Adjacent intervals are merged.
+    pub fn merge<I: IntoIterator<Item = Interval<T>>>(intervals: I) -> Vec<Interval<T>> {
+        let mut sorted: Vec<Interval<T>> = intervals.into_iter().collect();
+        sorted.sort();
+
+        let mut merged: Vec<Interval<T>> = Vec::with_capacity(sorted.len());
+        for interval in sorted {
+            if let Some(last) = merged.last_mut() {
+                if let Union::Merged(union) = last.union(&interval) {
+                    *last = union;
+                    continue;
+                }
+            }
+            merged.push(interval);
+        }
+        merged
+    }
+}
+
+impl<T: Bound> TryFrom<RangeInclusive<T>> for Interval<T> {
+    type Error = IntervalError;
+
+    fn try_from(range: RangeInclusive<T>) -> Result<Self, Self::Error> {
+        let (start, end) = range.into_inner();
+        Interval::new(start, end)
+    }
+}
+
+impl<T: Bound> TryFrom<Range<T>> for Interval<T> {
+    type Error = IntervalError;
+
+    fn try_from(range: Range<T>) -> Result<Self, Self::Error> {
+        if range.start > range.end {
+            return Err(IntervalError::Inverted);
+        }
+        match range.end.predecessor() {
+            Some(end) if range.start <= end => Interval::new(range.start, end),
+            _ => Err(IntervalError::Empty),
+        }
+    }
+}
+
+impl<T: Bound> fmt::Display for Interval<T> {
+    /// Format the interval in the puzzle's `start-end` format.
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}-{}", self.start, self.end)
+    }
+}
+
+/// Tests for the interval set algebra.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn interval(start: i64, end: i64) -> Interval<i64> {
+        Interval::new(start, end).unwrap()
+    }
+
+    /// Test that empty and inverted intervals are rejected.
+    #[test]
+    fn test_interval_creation() {
+        assert_eq!(Interval::new(4u64, 2), Err(IntervalError::Inverted));
+        assert_eq!(Interval::try_from(3u64..3), Err(IntervalError::Empty));
+        assert_eq!(Interval::try_from(Range { start: 5i32, end: 3 }), Err(IntervalError::Inverted));
+        assert_eq!(Interval::try_from(-2i32..3), Ok(Interval::new(-2, 2).unwrap()));
+        assert_eq!(Interval::try_from(1u64..=1).map(|interval| interval.length()), Ok(1));
+        assert_eq!(Interval::new(u64::MIN, u64::MAX).unwrap().length(), u64::MAX);
+    }
+
+    /// Test intersections, unions and differences between pairs of intervals.
+    #[test]
+    fn test_interval_algebra() {
+        assert_eq!(interval(-3, 4).length(), 8);
+        assert_eq!(interval(-3, 4).intersection(&interval(2, 9)), Some(interval(2, 4)));
+        assert_eq!(interval(-3, 4).intersection(&interval(5, 9)), None);
+
+        assert_eq!(interval(1, 4).union(&interval(5, 9)), Union::Merged(interval(1, 9)));
+        assert_eq!(
+            interval(6, 9).union(&interval(1, 4)),
+            Union::Disjoint(interval(1, 4), interval(6, 9))
+        );
+
+        assert_eq!(
+            interval(1, 9).difference(&interval(3, 4)),
+            vec![interval(1, 2), interval(5, 9)]
+        );
+        assert_eq!(interval(1, 9).difference(&interval(-5, 4)), vec![interval(5, 9)]);
+        assert_eq!(interval(1, 9).difference(&interval(0, 10)), vec![]);
+        assert_eq!(interval(1, 9).difference(&interval(10, 12)), vec![interval(1, 9)]);
+    }
+
+    /// Test that merging a list of intervals sorts them and joins overlapping or adjacent ones.
+    #[test]
+    fn test_interval_merge() {
+        let merged = Interval::merge(vec![
+            interval(10, 12),
+            interval(1, 3),
+            interval(2, 5),
+            interval(6, 6),
+            interval(14, 20),
+            interval(15, 16),
+        ]);
+        assert_eq!(merged, vec![interval(1, 6), interval(10, 12), interval(14, 20)]);
+    }
+}
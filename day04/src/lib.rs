@@ -0,0 +1,70 @@
+//! Parsing and analysis of the cleaning assignments for the fourth advent of code problem.
+pub mod coverage;
+pub mod graph;
+pub mod group;
+pub mod index;
+pub mod interval;
+pub mod optimise;
+pub mod render;
+pub mod sweep;
+
+use interval::Interval;
+
+/// The example assignments from the puzzle text.
+#[cfg(test)]
+pub(crate) const EXAMPLE: &str = "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8\n";
+
+/// A range of sectors that the elves need to clean.
+pub type CleaningRange = Interval<usize>;
+
+/// A cleaning range, and where it appears in the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Assignment {
+    /// The line number (counting from one) of the range in the input.
+    pub line: usize,
+    /// The (zero-based) position of the range within its line.
+    pub position: usize,
+    pub range: CleaningRange,
+}
+
+/// Parse a single hyphen-separated cleaning range.
+pub fn parse_range(range: &str) -> Result<CleaningRange, &'static str> {
+    let (start, end) = range.split_once('-').ok_or("Expected a hyphen-separated range")?;
+    let start = start.parse::<usize>().map_err(|_| "Expected an integer range start")?;
+    let end = end.parse::<usize>().map_err(|_| "Expected an integer range end")?;
+    Interval::new(start, end).map_err(|_| "Expected the range start to be before its end")
+}
+
+/// Parse an input string containg lines of comma-separated cleaning ranges (which are themselves
+/// two hyphen-separated integers), returning the group of ranges on each line.
+pub fn parse_range_groups(input_string: &str) -> Result<Vec<Vec<CleaningRange>>, &'static str> {
+    let mut range_groups: Vec<Vec<CleaningRange>> = Vec::new();
+
+    for line in input_string.split('\n') {
+        if line.is_empty() {
+            continue;
+        };
+
+        let ranges: Result<Vec<CleaningRange>, &'static str> =
+            line.split(',').map(parse_range).collect();
+        range_groups.push(ranges?);
+    }
+    Ok(range_groups)
+}
+
+/// Parse every comma-separated cleaning range in the input, recording where each was found.
+pub fn parse_assignments(input_string: &str) -> Result<Vec<Assignment>, &'static str> {
+    let mut assignments: Vec<Assignment> = Vec::new();
+
+    for (line_index, line) in input_string.split('\n').enumerate() {
+        if line.is_empty() {
+            continue;
+        };
+
+        for (position, range) in line.split(',').enumerate() {
+            let range = parse_range(range)?;
+            assignments.push(Assignment { line: line_index + 1, position, range });
+        }
+    }
+    Ok(assignments)
+}
@@ -1,64 +1,292 @@
 //! Solution to the fourth advent of code problem.
+use std::env;
 use std::fs::read_to_string;
+use std::io::{stdin, BufRead};
+use std::num::NonZeroUsize;
 use std::path::Path;
+use std::process;
 
-/// A range of sectors that the elves need to clean.
-struct CleaningRange {
-    start: usize,
-    end: usize,
+use day04::coverage::CoverageReport;
+use day04::graph::OverlapGraph;
+use day04::group::GroupReport;
+use day04::index::AssignmentIndex;
+use day04::optimise::Proposal;
+use day04::render::{render_group, RenderOptions};
+use day04::sweep::{find_overlapping_pairs, OverlapKind};
+use day04::{parse_assignments, parse_range, parse_range_groups, Assignment, CleaningRange};
+
+/// Print the number of groups of ranges on each line which replicate each other's work.
+fn print_group_answers(input_string: &str) {
+    let range_groups = parse_range_groups(input_string).expect("Unable to parse cleaning ranges");
+    let reports: Vec<GroupReport> =
+        range_groups.iter().map(|group| GroupReport::new(group)).collect();
+
+    let replicating_groups = reports.iter().filter(|report| report.replicates()).count();
+    println!("{} groups of ranges completely replicate the work of an elf", replicating_groups);
+
+    let overlapping_groups = reports.iter().filter(|report| report.overlaps()).count();
+    println!("{} groups of ranges replicate some work of an elf", overlapping_groups);
 }
 
-impl CleaningRange {
-    /// Whether the cleaning range duplicates _any_
-    /// work done in the other range.
-    fn overlaps(&self, other: &CleaningRange) -> bool {
-        self.start <= other.end && self.end >= other.start
+/// Print how the ranges in each group duplicate each other's work.
+fn print_group_reports(input_string: &str) {
+    let assignments = parse_assignments(input_string).expect("Unable to parse cleaning ranges");
+
+    let mut total_redundancy = 0;
+    for group in assignments.chunk_by(|first, second| first.line == second.line) {
+        let ranges: Vec<CleaningRange> = group.iter().map(|assignment| assignment.range).collect();
+        let report = GroupReport::new(&ranges);
+        total_redundancy += report.redundancy;
+
+        let labels: Vec<String> = ranges.iter().map(CleaningRange::to_string).collect();
+        println!(
+            "line {} ({}): covered by others {:?}, overlapping {:?}, redundancy {}",
+            group[0].line,
+            labels.join(","),
+            report.covered,
+            report.overlapping,
+            report.redundancy
+        );
     }
+    println!("{} section cleanings duplicate work within a group", total_redundancy);
+}
+
+/// Print trimmed ranges for each line which duplicates work, and the sections they would save.
+fn print_proposals(input_string: &str) {
+    let assignments = parse_assignments(input_string).expect("Unable to parse cleaning ranges");
 
-    /// Whether the cleaning range fully contains the other.
-    fn contains(&self, other: &CleaningRange) -> bool {
-        other.start >= self.start && other.end <= self.end
+    let mut total_saved = 0;
+    for group in assignments.chunk_by(|first, second| first.line == second.line) {
+        let ranges: Vec<CleaningRange> = group.iter().map(|assignment| assignment.range).collect();
+        let proposal = Proposal::new(&ranges);
+        if proposal.sections_saved == 0 {
+            continue;
+        }
+        total_saved += proposal.sections_saved;
+
+        let labels: Vec<String> = ranges.iter().map(CleaningRange::to_string).collect();
+        println!(
+            "line {}: {} -> {} (saves {} sections)",
+            group[0].line,
+            labels.join(","),
+            proposal,
+            proposal.sections_saved
+        );
     }
+    println!("Trimming the assignments would save {} section cleanings", total_saved);
+}
 
-    /// Whether the cleaning range completely replicates
-    /// work done in the other range.
-    fn replicates(&self, other: &CleaningRange) -> bool {
-        self.contains(other) || other.contains(self)
+/// Print every pair of assignments in the input which overlap, regardless of line.
+fn print_overlapping_pairs(input_string: &str) {
+    let assignments = parse_assignments(input_string).expect("Unable to parse cleaning ranges");
+    let pairs = find_overlapping_pairs(&assignments);
+
+    for pair in &pairs {
+        let relation = match pair.kind {
+            OverlapKind::Partial => "overlaps",
+            OverlapKind::Contains => "contains",
+        };
+        println!(
+            "line {} range {} ({}) {} line {} range {} ({})",
+            pair.first.line,
+            pair.first.position,
+            pair.first.range,
+            relation,
+            pair.second.line,
+            pair.second.position,
+            pair.second.range
+        );
     }
+
+    let n_containing = pairs.iter().filter(|pair| pair.kind == OverlapKind::Contains).count();
+    println!(
+        "{} pairs of assignments overlap, of which {} fully contain the other",
+        pairs.len(),
+        n_containing
+    );
 }
 
-/// Parse an input string containg comma-separated cleaning ranges (which are themselves two
-/// hyphen-separated integers).
-fn parse_range_pairs(input_string: String) -> Vec<(CleaningRange, CleaningRange)> {
-    let mut range_pairs: Vec<(CleaningRange, CleaningRange)> = Vec::new();
+/// Print a report on how many sections are covered, and how much work is duplicated.
+fn print_coverage_report(input_string: &str) {
+    let assignments = parse_assignments(input_string).expect("Unable to parse cleaning ranges");
+    let report = CoverageReport::new(assignments.iter().map(|assignment| &assignment.range));
 
-    for line in input_string.split('\n') {
-        if line.is_empty() {
+    let format_ranges = |ranges: &[CleaningRange]| -> String {
+        let ranges: Vec<String> = ranges.iter().map(CleaningRange::to_string).collect();
+        if ranges.is_empty() {
+            "none".to_string()
+        } else {
+            ranges.join(", ")
+        }
+    };
+
+    println!("{} distinct sections are covered", report.total_sections());
+    println!("Gaps in the covered sections: {}", format_ranges(&report.gaps));
+    println!(
+        "At most {} elves are assigned to one section, in sections {}",
+        report.max_elves,
+        format_ranges(&report.busiest)
+    );
+    for (n_elves, n_sections) in &report.histogram {
+        println!("{:>4} sections are covered by {} elves", n_sections, n_elves);
+    }
+    println!(
+        "{} section cleanings in total, of which {} duplicate another elf's work",
+        report.total_work(),
+        report.duplicated_work()
+    );
+}
+
+/// Draw the assignments on each line, as in the puzzle text.
+///
+/// Accepts the options `--window <first>-<last>` to choose the sections to draw, `--wrap <n>`
+/// to draw at most n sections per row and `--lines <first>-<last>` to choose the lines to draw.
+fn print_rendered_assignments(input_string: &str, args: &[String]) -> Result<(), &'static str> {
+    let mut options = RenderOptions::default();
+    let mut lines: Option<CleaningRange> = None;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or("Expected a value after each option")?;
+        match flag.as_str() {
+            "--window" => options.window = Some(parse_range(value)?),
+            "--wrap" => {
+                let wrap = value.parse::<NonZeroUsize>().map_err(|_| "Expected a positive wrap")?;
+                options.wrap = Some(wrap);
+            }
+            "--lines" => lines = Some(parse_range(value)?),
+            _ => return Err("Expected one of '--window', '--wrap' or '--lines'"),
+        }
+    }
+
+    let assignments = parse_assignments(input_string)?;
+    for group in assignments.chunk_by(|first, second| first.line == second.line) {
+        let line = group[0].line;
+        if lines.is_some_and(|lines| !lines.includes(line)) {
             continue;
-        };
+        }
+
+        let ranges: Vec<CleaningRange> = group.iter().map(|assignment| assignment.range).collect();
+        println!("Line {}:\n{}", line, render_group(&ranges, &options));
+    }
+    Ok(())
+}
+
+/// Print the structure of the graph of overlapping assignments, or export it in the DOT format
+/// if `--dot` is given.
+fn print_overlap_graph(input_string: &str, args: &[String]) {
+    let assignments = parse_assignments(input_string).expect("Unable to parse cleaning ranges");
+    let graph = OverlapGraph::new(&assignments);
+
+    if args.first().map(String::as_str) == Some("--dot") {
+        print!("{}", graph.to_dot());
+        return;
+    }
+
+    let components = graph.components();
+    println!(
+        "{} assignments and {} overlaps form {} clusters",
+        graph.nodes.len(),
+        graph.edges.len(),
+        components.len()
+    );
+    if let Some(largest) = graph.largest_component() {
+        let first = &graph.nodes[largest[0]];
+        println!(
+            "The largest cluster has {} assignments, starting at line {} range {} ({})",
+            largest.len(),
+            first.line,
+            first.position,
+            first.range
+        );
+    }
+    println!("The assignments can be split into {} shifts without overlap", graph.n_colours());
+}
 
-        let ranges: Vec<Vec<usize>> = line
-            .split(',')
-            .map(|range| range.split('-').map(|string| string.parse::<usize>().unwrap()).collect())
-            .collect();
+/// Describe the assignments found by a query, in the order they appear in the input.
+fn describe_assignments(mut found: Vec<&Assignment>) -> String {
+    found.sort_by_key(|assignment| (assignment.line, assignment.position));
+    let found: Vec<String> = found
+        .iter()
+        .map(|assignment| {
+            format!("line {} range {} ({})", assignment.line, assignment.position, assignment.range)
+        })
+        .collect();
+    found.join("; ")
+}
 
-        let first_range = CleaningRange { start: ranges[0][0], end: ranges[0][1] };
-        let second_range = CleaningRange { start: ranges[1][0], end: ranges[1][1] };
-        range_pairs.push((first_range, second_range));
+/// Answer a single query using the index.
+fn answer_query(index: &AssignmentIndex, query: &str) -> Result<(), &'static str> {
+    match query.split_once(' ') {
+        Some(("section", section)) => {
+            let section = section.parse::<usize>().map_err(|_| "Expected an integer section")?;
+            let found = index.cleaning(section);
+            println!(
+                "{} elves clean section {}: {}",
+                found.len(),
+                section,
+                describe_assignments(found)
+            );
+        }
+        Some(("range", range)) => {
+            let range = parse_range(range)?;
+            let found = index.intersecting(&range);
+            println!(
+                "{} assignments intersect {}: {}",
+                found.len(),
+                range,
+                describe_assignments(found)
+            );
+        }
+        Some(("coverage", range)) => {
+            let range = parse_range(range)?;
+            for (segment, n_elves) in index.coverage(&range) {
+                println!("sections {}: {} elves", segment, n_elves);
+            }
+        }
+        _ => return Err("Expected one of 'section <s>', 'range <a>-<b>' or 'coverage <a>-<b>'"),
     }
-    range_pairs
+    Ok(())
+}
+
+/// Answer queries read from stdin, one per line, using an index over the assignments. Queries
+/// which cannot be answered are reported, and the following queries are still answered.
+///
+/// Supported queries are:
+///  - `section <s>`: which elves clean section s.
+///  - `range <a>-<b>`: which assignments share any sections with the range a-b.
+///  - `coverage <a>-<b>`: how many elves clean each part of the range a-b.
+fn answer_queries(input_string: &str) -> Result<(), &'static str> {
+    let assignments = parse_assignments(input_string)?;
+    let index = AssignmentIndex::new(&assignments);
+
+    for query in stdin().lock().lines() {
+        let query = query.map_err(|_| "Unable to read query from stdin")?;
+        if let Err(error) = answer_query(&index, &query) {
+            eprintln!("Unable to answer query '{}': {}", query, error);
+        }
+    }
+    Ok(())
 }
 
 fn main() {
     let path = Path::new("./input_01.txt");
     let input_string = read_to_string(path).expect("Unable to read input file 'input_01.txt'");
-    let range_pairs = parse_range_pairs(input_string);
 
-    let replicating_pairs: usize =
-        range_pairs.iter().map(|(range, other)| range.replicates(other) as usize).sum();
-    println!("{} pairs of ranges completely replicate the work of the other", replicating_pairs);
-
-    let overlapping_pairs: usize =
-        range_pairs.iter().map(|(range, other)| range.overlaps(other) as usize).sum();
-    println!("{} pairs of ranges replicate some work of the other", overlapping_pairs);
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => print_group_answers(&input_string),
+        Some("groups") => print_group_reports(&input_string),
+        Some("optimise") => print_proposals(&input_string),
+        Some("overlaps") => print_overlapping_pairs(&input_string),
+        Some("coverage") => print_coverage_report(&input_string),
+        Some("graph") => print_overlap_graph(&input_string, &args[1..]),
+        Some("query") => answer_queries(&input_string).expect("Unable to answer queries"),
+        Some("render") => print_rendered_assignments(&input_string, &args[1..])
+            .expect("Unable to render cleaning ranges"),
+        Some(_) => {
+            eprintln!("Usage: day04 [groups|optimise|overlaps|coverage|graph|query|render]");
+            process::exit(2);
+        }
+    }
 }
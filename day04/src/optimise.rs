@@ -0,0 +1,106 @@
+//! Proposals for trimming a group's cleaning ranges so that no work is duplicated.
+use std::cmp::Reverse;
+use std::fmt;
+
+use crate::interval::Interval;
+use crate::CleaningRange;
+
+/// Trimmed cleaning ranges for a group, covering the same sections without any overlap.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Proposal {
+    /// The trimmed range for each elf, in the group's order. Elves whose work is entirely done by
+    /// others in the group are given no range.
+    pub ranges: Vec<Option<CleaningRange>>,
+    /// The number of section cleanings saved by the trimmed ranges.
+    pub sections_saved: u64,
+}
+
+impl Proposal {
+    /// Propose trimmed ranges for a group.
+    ///
+    /// The ranges are swept in order of their start (longest first, where they start together),
+    /// keeping track of the contiguous run of sections already assigned. A range contained by the
+    /// run is dropped, a range overlapping the run is trimmed to start after it, and any other
+    /// range is kept as-is. Each elf is left with a single range which is a part of their
+    /// original one, and only the start of a range is ever moved.
+    pub fn new(group: &[CleaningRange]) -> Proposal {
+        let mut order: Vec<usize> = (0..group.len()).collect();
+        order.sort_by_key(|position| (group[*position].start(), Reverse(group[*position].end())));
+
+        let mut ranges: Vec<Option<CleaningRange>> = vec![None; group.len()];
+        let mut run: Option<CleaningRange> = None;
+
+        for position in order {
+            let range = group[position];
+            let trimmed = match run {
+                Some(assigned) if assigned.contains(&range) => None,
+                Some(assigned) if assigned.overlaps(&range) => {
+                    run = Interval::new(assigned.start(), range.end()).ok();
+                    range.difference(&assigned).first().copied()
+                }
+                _ => {
+                    run = Some(range);
+                    Some(range)
+                }
+            };
+            ranges[position] = trimmed;
+        }
+
+        let original: u64 = group.iter().map(Interval::length).sum();
+        let proposed: u64 = ranges.iter().flatten().map(Interval::length).sum();
+        Proposal { ranges, sections_saved: original - proposed }
+    }
+}
+
+impl fmt::Display for Proposal {
+    /// Format the trimmed ranges in the input's `a-b,c-d` format, with a `-` for each dropped
+    /// range so that every position still matches its elf.
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges: Vec<String> = self
+            .ranges
+            .iter()
+            .map(|range| range.map_or("-".to_string(), |range| range.to_string()))
+            .collect();
+        write!(formatter, "{}", ranges.join(","))
+    }
+}
+
+/// Tests for the assignment optimiser.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_range_groups, EXAMPLE};
+
+    /// Test that proposals cover the same sections as the original ranges, without overlap.
+    #[test]
+    fn test_proposals() {
+        let example = format!("{}1-5,3-8,4-10,12-12\n", EXAMPLE);
+        let groups = parse_range_groups(&example).unwrap();
+        let proposals: Vec<Proposal> = groups.iter().map(|group| Proposal::new(group)).collect();
+
+        let lines: Vec<String> = proposals.iter().map(Proposal::to_string).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "2-4,6-8",
+                "2-3,4-5",
+                "5-7,8-9",
+                "2-8,-",
+                "-,4-6",
+                "2-6,7-8",
+                "1-5,6-8,9-10,12-12"
+            ]
+        );
+
+        let saved: Vec<u64> = proposals.iter().map(|proposal| proposal.sections_saved).collect();
+        assert_eq!(saved, vec![0, 0, 1, 5, 1, 3, 8]);
+
+        for (group, proposal) in groups.iter().zip(&proposals) {
+            let trimmed: Vec<CleaningRange> = proposal.ranges.iter().flatten().copied().collect();
+            assert_eq!(Interval::merge(trimmed.iter().copied()), Interval::merge(group.clone()));
+            for (position, range) in trimmed.iter().enumerate() {
+                assert!(trimmed[position + 1..].iter().all(|other| !range.overlaps(other)));
+            }
+        }
+    }
+}
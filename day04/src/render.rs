@@ -0,0 +1,127 @@
+//! Rendering of cleaning assignments as the rows of dots and section numbers from the puzzle.
+use std::num::NonZeroUsize;
+
+use crate::interval::Interval;
+use crate::CleaningRange;
+
+/// The marker drawn under sections which are cleaned by more than one elf.
+const OVERLAP_MARKER: char = '#';
+/// The most sections drawn when no window is given, so that very long ranges stay readable.
+const MAX_DEFAULT_SECTIONS: usize = 1000;
+
+/// Options controlling which sections are drawn, and how.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// The sections to draw. By default, every section from one (or zero, if it is assigned) up
+    /// to the last assigned section is drawn, up to a thousand sections.
+    pub window: Option<CleaningRange>,
+    /// The maximum number of sections to draw on one row before wrapping onto another.
+    pub wrap: Option<NonZeroUsize>,
+}
+
+/// Render a group of cleaning ranges (usually a pair from one line) as aligned rows, with each
+/// range labelled in the input format. Sections covered by more than one range are marked with a
+/// `#` on an extra row underneath.
+///
+/// Where a section number has fewer digits than the largest section in the window it is padded
+/// with zeros, and sections are separated by spaces so that each remains readable.
+pub fn render_group(ranges: &[CleaningRange], options: &RenderOptions) -> String {
+    let window = match options.window.or_else(|| default_window(ranges)) {
+        Some(window) => window,
+        None => return String::new(),
+    };
+    let width = window.end().to_string().len();
+    let separator = if width > 1 { " " } else { "" };
+
+    let chunk_size = options.wrap.map_or(usize::MAX, NonZeroUsize::get);
+
+    let mut blocks: Vec<String> = Vec::new();
+    let mut chunk_start = Some(window.start());
+    while let Some(start) = chunk_start {
+        let end = start.saturating_add(chunk_size - 1).min(window.end());
+        chunk_start = if end < window.end() { Some(end + 1) } else { None };
+
+        let mut block = String::new();
+        for range in ranges {
+            let cells: Vec<String> = (start..=end)
+                .map(|section| {
+                    if range.includes(section) {
+                        format!("{:0width$}", section, width = width)
+                    } else {
+                        ".".repeat(width)
+                    }
+                })
+                .collect();
+            block.push_str(&format!("{}  {}\n", cells.join(separator), range));
+        }
+
+        let overlapping: Vec<bool> = (start..=end)
+            .map(|section| ranges.iter().filter(|range| range.includes(section)).count() > 1)
+            .collect();
+        if overlapping.contains(&true) {
+            let cells: Vec<String> = overlapping
+                .iter()
+                .map(|overlaps| {
+                    let marker = if *overlaps { OVERLAP_MARKER } else { ' ' };
+                    marker.to_string().repeat(width)
+                })
+                .collect();
+            block.push_str(&format!("{}  overlap\n", cells.join(separator)));
+        }
+        blocks.push(block);
+    }
+    blocks.join("\n")
+}
+
+/// The window from section one (or zero) to the last section in any of the ranges, drawing at
+/// most `MAX_DEFAULT_SECTIONS` sections.
+fn default_window(ranges: &[CleaningRange]) -> Option<CleaningRange> {
+    let first = ranges.iter().map(Interval::start).min()?.min(1);
+    let last = ranges.iter().map(Interval::end).max()?;
+    Interval::new(first, last.min(first + (MAX_DEFAULT_SECTIONS - 1))).ok()
+}
+
+/// Tests for the assignment renderer.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_range;
+
+    fn ranges(line: &str) -> Vec<CleaningRange> {
+        line.split(',').map(|range| parse_range(range).unwrap()).collect()
+    }
+
+    /// Test that a pair is drawn as in the puzzle text.
+    #[test]
+    fn test_render_pair() {
+        let options = RenderOptions { window: Some(Interval::new(1, 9).unwrap()), wrap: None };
+        assert_eq!(render_group(&ranges("2-4,6-8"), &options), ".234.....  2-4\n.....678.  6-8\n");
+        assert_eq!(
+            render_group(&ranges("5-7,7-9"), &options),
+            "....567..  5-7\n......789  7-9\n      #    overlap\n"
+        );
+    }
+
+    /// Test that multi-digit sections are padded and that long windows wrap.
+    #[test]
+    fn test_render_wrapped() {
+        let options = RenderOptions { window: None, wrap: NonZeroUsize::new(6) };
+        let expected = "\
+01 02 03 04 .. ..  1-4
+.. .. .. 04 05 06  4-11
+         ##        overlap
+
+.. .. .. .. ..  1-4
+07 08 09 10 11  4-11
+";
+        assert_eq!(render_group(&ranges("1-4,4-11"), &options), expected);
+    }
+
+    /// Test that the default window is clamped for very long ranges.
+    #[test]
+    fn test_render_long_range() {
+        let rendered = render_group(&ranges("1-1000000000"), &RenderOptions::default());
+        assert!(rendered.starts_with("0001 0002 "));
+        assert!(rendered.ends_with(" 1000  1-1000000000\n"));
+    }
+}
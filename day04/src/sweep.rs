@@ -0,0 +1,138 @@
+//! Detection of overlapping assignments across the whole input, using a sweep line.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::Assignment;
+
+/// How a pair of assignments overlap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlapKind {
+    /// The assignments share some sections.
+    Partial,
+    /// The first assignment fully contains the second.
+    Contains,
+}
+
+/// A pair of assignments which duplicate some work.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverlappingPair<'a> {
+    /// The assignment which starts first (or, if they start together, ends last).
+    pub first: &'a Assignment,
+    pub second: &'a Assignment,
+    pub kind: OverlapKind,
+}
+
+/// Find every pair of assignments which overlap, in O(n log n + k) for n assignments and k pairs.
+pub fn find_overlapping_pairs(assignments: &[Assignment]) -> Vec<OverlappingPair<'_>> {
+    find_overlapping_indices(assignments)
+        .into_iter()
+        .map(|(first, second, kind)| OverlappingPair {
+            first: &assignments[first],
+            second: &assignments[second],
+            kind,
+        })
+        .collect()
+}
+
+/// Find the indices of every pair of assignments which overlap, with the index of the assignment
+/// which starts first (or, if they start together, ends last) given first.
+///
+/// The assignments are swept in order of their start, keeping a min-heap of the ends of the
+/// assignments which are still open. When an assignment is reached, any open assignments which
+/// ended before it are retired, and every assignment left open must overlap it.
+pub fn find_overlapping_indices(assignments: &[Assignment]) -> Vec<(usize, usize, OverlapKind)> {
+    let mut order: Vec<usize> = (0..assignments.len()).collect();
+    order.sort_by_key(|index| {
+        let range = &assignments[*index].range;
+        (range.start(), Reverse(range.end()))
+    });
+
+    let mut pairs: Vec<(usize, usize, OverlapKind)> = Vec::new();
+    let mut open: BinaryHeap<Reverse<(usize, usize)>> = BinaryHeap::new();
+
+    for index in order {
+        let range = &assignments[index].range;
+        while let Some(Reverse((end, _))) = open.peek() {
+            if *end >= range.start() {
+                break;
+            }
+            open.pop();
+        }
+
+        for Reverse((end, open_index)) in open.iter() {
+            let kind =
+                if *end >= range.end() { OverlapKind::Contains } else { OverlapKind::Partial };
+            pairs.push((*open_index, index, kind));
+        }
+        open.push(Reverse((range.end(), index)));
+    }
+    pairs
+}
+
+/// Tests for the sweep line overlap detection.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::CleaningRange;
+
+    /// Test that the sweep finds the same pairs as comparing every pair of assignments.
+    #[test]
+    fn test_sweep_matches_brute_force() {
+        let mut seed: u64 = 2022;
+        let mut next = |modulus: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((seed >> 33) % modulus) as usize
+        };
+
+        let assignments: Vec<Assignment> = (0..200)
+            .map(|index| {
+                let start = next(100);
+                let end = start + next(15);
+                let range = CleaningRange::new(start, end).unwrap();
+                Assignment { line: index / 2 + 1, position: index % 2, range }
+            })
+            .collect();
+
+        let mut expected: Vec<(usize, usize, usize, usize, bool)> = Vec::new();
+        for (index, first) in assignments.iter().enumerate() {
+            for second in &assignments[index + 1..] {
+                if first.range.overlaps(&second.range) {
+                    let (first, second) = if (first.range.start(), second.range.end())
+                        <= (second.range.start(), first.range.end())
+                    {
+                        (first, second)
+                    } else {
+                        (second, first)
+                    };
+                    let contains = first.range.contains(&second.range);
+                    expected.push((
+                        first.line,
+                        first.position,
+                        second.line,
+                        second.position,
+                        contains,
+                    ));
+                }
+            }
+        }
+
+        let mut found: Vec<(usize, usize, usize, usize, bool)> =
+            find_overlapping_pairs(&assignments)
+                .iter()
+                .map(|pair| {
+                    let contains = pair.kind == OverlapKind::Contains;
+                    (
+                        pair.first.line,
+                        pair.first.position,
+                        pair.second.line,
+                        pair.second.position,
+                        contains,
+                    )
+                })
+                .collect();
+
+        expected.sort();
+        found.sort();
+        assert_eq!(found, expected);
+    }
+}
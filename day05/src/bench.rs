@@ -0,0 +1,139 @@
+//! Generation of large puzzle inputs, and the original linked list stacks for benchmarking the
+//! bulk moves against.
+use std::collections::{HashMap, LinkedList};
+
+use day05::drawing::render_drawing;
+use day05::stacks::{Crate, StackId, Stacks};
+use day05::Instruction;
+
+/// A linear congruential generator, so that generated inputs are reproducible.
+struct Random(u64);
+
+impl Random {
+    /// A pseudo-random number below the bound.
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as usize
+    }
+}
+
+/// Generate puzzle input with `n_stacks` stacks of `n_crates` crates each, followed by
+/// `n_instructions` instructions which are valid with either crane. There must be at least two
+/// stacks.
+pub fn generate_input(n_stacks: u32, n_crates: usize, n_instructions: usize, seed: u64) -> String {
+    let mut random = Random(seed);
+    let ids: Vec<StackId> = (1..=n_stacks).collect();
+    let mut stacks = Stacks::new(ids.iter().copied());
+    for id in &ids {
+        for _ in 0..n_crates {
+            let label = char::from(b'A' + random.below(26) as u8).to_string();
+            stacks.push(*id, Crate::new(&label)).expect("Stack is known");
+        }
+    }
+
+    // Both cranes leave the same number of crates in each stack, so track only the heights.
+    let mut heights: Vec<usize> = vec![n_crates; ids.len()];
+    let mut input = render_drawing(&stacks);
+    input.push('\n');
+    for _ in 0..n_instructions {
+        let sources: Vec<usize> = (0..ids.len()).filter(|index| heights[*index] > 0).collect();
+        let Some(source) = sources.get(random.below(sources.len().max(1))).copied() else {
+            break;
+        };
+        let destination = (source + 1 + random.below(ids.len() - 1)) % ids.len();
+        let n_crates = 1 + random.below(heights[source]);
+        heights[source] -= n_crates;
+        heights[destination] += n_crates;
+
+        let instruction =
+            Instruction { n_crates, move_from: ids[source], move_to: ids[destination] };
+        input.push_str(&format!("{}\n", instruction));
+    }
+    input
+}
+
+/// The stacks as they were held before `Stacks` was introduced: a linked list of crate
+/// drawings (e.g. `[Z]`) for each stack, from bottom to top.
+pub type BaselineStacks = HashMap<u32, LinkedList<String>>;
+
+/// Copy the stacks into the baseline representation.
+pub fn to_baseline(stacks: &Stacks) -> BaselineStacks {
+    let crates = |id: StackId| stacks.stack(id).expect("Stack is known").iter();
+    stacks
+        .ids()
+        .map(|id| (id, crates(id).map(|some_crate| format!("[{}]", some_crate)).collect()))
+        .collect()
+}
+
+/// Copy the stacks back out of the baseline representation.
+pub fn from_baseline(baseline: &BaselineStacks) -> Stacks {
+    let mut ids: Vec<StackId> = baseline.keys().copied().collect();
+    ids.sort_unstable();
+    let mut stacks = Stacks::new(ids.iter().copied());
+    for id in ids {
+        for drawing in &baseline[&id] {
+            let some_crate = Crate::parse(drawing).expect("Crate drawing is valid");
+            stacks.push(id, some_crate).expect("Stack is known");
+        }
+    }
+    stacks
+}
+
+/// Apply the instruction to the baseline stacks as the original solution did, popping and
+/// pushing one crate at a time for the CrateMover 9000, or popping the crates into a temporary
+/// `Vec` and pushing them back in their original order for the CrateMover 9001.
+///
+/// Panics if the instruction is not possible.
+pub fn apply_baseline(instruction: &Instruction, stacks: &mut BaselineStacks, multi: bool) {
+    if !multi {
+        for _ in 0..instruction.n_crates {
+            let source_stack = stacks.get_mut(&instruction.move_from).unwrap();
+            let some_crate = source_stack.pop_back().unwrap();
+
+            let dest_stack = stacks.get_mut(&instruction.move_to).unwrap();
+            dest_stack.push_back(some_crate);
+        }
+        return;
+    }
+
+    let mut to_move = Vec::with_capacity(instruction.n_crates);
+    let source_stack = stacks.get_mut(&instruction.move_from).unwrap();
+    for _ in 0..instruction.n_crates {
+        let some_crate = source_stack.pop_back().unwrap();
+        to_move.push(some_crate);
+    }
+
+    let dest_stack = stacks.get_mut(&instruction.move_to).unwrap();
+    for some_crate in to_move.into_iter().rev() {
+        dest_stack.push_back(some_crate);
+    }
+}
+
+/// Tests for the input generator and baseline stacks.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use day05::crane::crate_mover;
+    use day05::parse_stacks_instructions;
+
+    /// Test that the bulk moves give the same stacks as the baseline on generated input.
+    #[test]
+    fn test_bulk_moves_match_baseline() {
+        let input = generate_input(11, 40, 500, 5);
+        let (start, instructions) = parse_stacks_instructions(&input).unwrap();
+        assert_eq!(start.ids().count(), 11);
+        assert_eq!(instructions.len(), 500);
+
+        assert_eq!(from_baseline(&to_baseline(&start)), start);
+
+        for multi in [false, true] {
+            let (mut bulk_stacks, mut baseline) = (start.clone(), to_baseline(&start));
+            let mut crane = crate_mover(multi);
+            for instruction in &instructions {
+                crane.apply(instruction, &mut bulk_stacks).unwrap();
+                apply_baseline(instruction, &mut baseline, multi);
+            }
+            assert_eq!(bulk_stacks, from_baseline(&baseline));
+        }
+    }
+}
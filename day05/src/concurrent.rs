@@ -0,0 +1,234 @@
+//! Simulation of several cranes working through their shares of the instructions at once.
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::num::NonZeroUsize;
+
+use crate::crane::Crane;
+use crate::debugger::StepError;
+use crate::stacks::{StackId, Stacks};
+use crate::Instruction;
+
+/// Two or more cranes which wanted to use the same stack in the same tick.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    /// The (one-based) tick of the conflict.
+    pub tick: usize,
+    pub stack: StackId,
+    /// The (zero-based) numbers of the cranes, in ascending order.
+    pub cranes: Vec<usize>,
+}
+
+/// An instruction which could not be applied when its crane reached it, because other cranes
+/// had already changed its stacks, and which was skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidMove {
+    /// The (one-based) tick in which the crane tried the instruction.
+    pub tick: usize,
+    /// The (zero-based) number of the crane.
+    pub crane: usize,
+    pub failure: StepError,
+}
+
+/// The result of running the cranes concurrently.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Simulation {
+    pub stacks: Stacks,
+    /// The (zero-based) indices of the instructions carried out in each tick.
+    pub schedule: Vec<Vec<usize>>,
+    pub conflicts: Vec<Conflict>,
+    pub invalid_moves: Vec<InvalidMove>,
+    /// Whether every instruction could be applied, and the final stacks are the same as when the
+    /// instructions are run one after another.
+    pub matches_serial: bool,
+}
+
+impl Simulation {
+    /// The number of ticks taken to carry out every instruction.
+    pub fn makespan(&self) -> usize {
+        self.schedule.len()
+    }
+}
+
+/// Run the instructions with `n_cranes` copies of the crane, with instruction `i` given to
+/// crane `i % n_cranes`.
+///
+/// In each tick, every crane wants to carry out its next instruction. Cranes which want the
+/// same stack are in conflict, and the one whose instruction comes first in the listing goes
+/// ahead while the others wait. Cranes do not otherwise wait for each other, so one crane may
+/// use a stack before an earlier instruction for it held by a crane which is further behind.
+/// An instruction which is no longer possible when its crane reaches it is recorded as an
+/// invalid move and skipped. The final stacks are compared with a serial run to check whether
+/// the order changed the result.
+///
+/// Fails if the instructions cannot be applied one after another.
+pub fn simulate<C: Crane + Clone>(
+    stacks: &Stacks,
+    instructions: &[Instruction],
+    n_cranes: NonZeroUsize,
+    crane: C,
+) -> Result<Simulation, StepError> {
+    let mut serial = stacks.clone();
+    let mut serial_crane = crane.clone();
+    for (index, instruction) in instructions.iter().enumerate() {
+        serial_crane.apply(instruction, &mut serial).map_err(|error| StepError {
+            index,
+            instruction: *instruction,
+            error,
+        })?;
+    }
+
+    let mut cranes: Vec<C> = vec![crane; n_cranes.get()];
+    let mut shares: Vec<VecDeque<usize>> = vec![VecDeque::new(); n_cranes.get()];
+    for index in 0..instructions.len() {
+        shares[index % n_cranes.get()].push_back(index);
+    }
+    let touched = |index: usize| {
+        let Instruction { move_from, move_to, .. } = instructions[index];
+        if move_from == move_to {
+            vec![move_from]
+        } else {
+            vec![move_from, move_to]
+        }
+    };
+
+    let mut simulation = Simulation {
+        stacks: stacks.clone(),
+        schedule: Vec::new(),
+        conflicts: Vec::new(),
+        invalid_moves: Vec::new(),
+        matches_serial: false,
+    };
+    while shares.iter().any(|share| !share.is_empty()) {
+        let tick = simulation.schedule.len() + 1;
+
+        let mut wanted: BTreeMap<StackId, Vec<usize>> = BTreeMap::new();
+        for (crane, share) in shares.iter().enumerate() {
+            for stack in share.front().map(|index| touched(*index)).unwrap_or_default() {
+                wanted.entry(stack).or_default().push(crane);
+            }
+        }
+        for (stack, cranes) in wanted {
+            if cranes.len() > 1 {
+                simulation.conflicts.push(Conflict { tick, stack, cranes });
+            }
+        }
+
+        // Instructions earlier in the listing claim their stacks first, and an instruction
+        // goes ahead only if none of its stacks have been claimed.
+        let mut fronts: Vec<usize> =
+            shares.iter().filter_map(|share| share.front().copied()).collect();
+        fronts.sort_unstable();
+        let mut claimed: BTreeSet<StackId> = BTreeSet::new();
+        let mut ready: Vec<usize> = Vec::new();
+        for index in fronts {
+            let stacks_used = touched(index);
+            let blocked = stacks_used.iter().any(|stack| claimed.contains(stack));
+            claimed.extend(stacks_used);
+            if !blocked {
+                ready.push(index);
+            }
+        }
+
+        for index in &ready {
+            let instruction = &instructions[*index];
+            let crane = index % n_cranes.get();
+            if let Err(error) = cranes[crane].apply(instruction, &mut simulation.stacks) {
+                let failure = StepError { index: *index, instruction: *instruction, error };
+                simulation.invalid_moves.push(InvalidMove { tick, crane, failure });
+            }
+            shares[crane].pop_front();
+        }
+        simulation.schedule.push(ready);
+    }
+
+    simulation.matches_serial = simulation.invalid_moves.is_empty() && serial == simulation.stacks;
+    Ok(simulation)
+}
+
+/// Tests for the concurrent crane simulation.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::formats::{parse_input, Format};
+    use crate::stacks::{Crate, StackError};
+    use crate::{parse_stacks_instructions, EXAMPLE};
+
+    /// Test the schedule and conflicts for the example, and cranes which work in parallel.
+    #[test]
+    fn test_simulate() {
+        let example = EXAMPLE.replace(" 1   2   3\n", " 1   2   3   4\n");
+        let (stacks, instructions) = parse_stacks_instructions(&example).unwrap();
+        let two = NonZeroUsize::new(2).unwrap();
+
+        let simulation = simulate(&stacks, &instructions, two, CrateMover9000).unwrap();
+        assert_eq!(simulation.schedule, vec![vec![0], vec![1], vec![2], vec![3]]);
+        let conflicts: Vec<(usize, StackId)> =
+            simulation.conflicts.iter().map(|conflict| (conflict.tick, conflict.stack)).collect();
+        assert_eq!(conflicts, vec![(1, 1), (2, 1), (3, 1), (3, 2)]);
+        assert!(simulation.matches_serial);
+
+        let parallel = [
+            Instruction { n_crates: 1, move_from: 1, move_to: 4 },
+            Instruction { n_crates: 2, move_from: 2, move_to: 3 },
+            Instruction { n_crates: 1, move_from: 4, move_to: 1 },
+            Instruction { n_crates: 3, move_from: 3, move_to: 2 },
+        ];
+        let simulation = simulate(&stacks, &parallel, two, CrateMover9001).unwrap();
+        assert_eq!(simulation.schedule, vec![vec![0, 1], vec![2, 3]]);
+        assert_eq!(simulation.makespan(), 2);
+        assert!(simulation.conflicts.is_empty());
+        assert!(simulation.matches_serial);
+    }
+
+    /// Test that a crane which falls behind can have a stack used out of order, changing the
+    /// result, and that errors in the serial run are reported.
+    #[test]
+    fn test_simulate_out_of_order() {
+        let input =
+            "1: AB\n2: C\n3: DE\n4: F\n5:\n\n1 1->2\n1 1->2\n1 3->4\n1 2->1\n1 4->3\n1 3->5\n";
+        let (stacks, listing) = parse_input(input, Format::Compact).unwrap();
+        let instructions: Vec<Instruction> =
+            listing.iter().map(|(_, instruction)| *instruction).collect();
+        let three = NonZeroUsize::new(3).unwrap();
+
+        let simulation = simulate(&stacks, &instructions, three, CrateMover9000).unwrap();
+        assert_eq!(simulation.schedule, vec![vec![0, 2], vec![1, 5], vec![3, 4]]);
+        assert_eq!(simulation.stacks.top(3).map(Crate::label), Ok("E"));
+        assert!(!simulation.matches_serial);
+
+        let simulation =
+            simulate(&stacks, &instructions, NonZeroUsize::new(2).unwrap(), CrateMover9000);
+        assert!(simulation.unwrap().matches_serial);
+
+        let instruction = Instruction { n_crates: 1, move_from: 5, move_to: 1 };
+        let error = simulate(&stacks, &[instruction], three, CrateMover9000).unwrap_err();
+        let not_enough = StackError::NotEnoughCrates { stack: 5, requested: 1, available: 0 };
+        assert_eq!((error.index, error.error), (0, not_enough));
+    }
+
+    /// Test that moves made impossible by other cranes are skipped on the puzzle input, and
+    /// that the simulation still finishes.
+    #[test]
+    fn test_simulate_invalid_moves() {
+        let (stacks, instructions) =
+            parse_stacks_instructions(include_str!("../input_01.txt")).unwrap();
+        let n_crates = |stacks: &Stacks| stacks.ids().map(|id| stacks.height(id).unwrap()).sum();
+        let total: usize = n_crates(&stacks);
+
+        for n_cranes in [3, 4, 9] {
+            let n_cranes = NonZeroUsize::new(n_cranes).unwrap();
+            let simulation = simulate(&stacks, &instructions, n_cranes, CrateMover9000).unwrap();
+            let n_scheduled: usize = simulation.schedule.iter().map(Vec::len).sum();
+            assert_eq!(n_scheduled, instructions.len());
+            assert!(!simulation.invalid_moves.is_empty());
+            assert!(!simulation.matches_serial);
+            assert_eq!(n_crates(&simulation.stacks), total);
+
+            for invalid_move in &simulation.invalid_moves {
+                let index = invalid_move.failure.index;
+                assert_eq!(invalid_move.crane, index % n_cranes.get());
+                assert!(simulation.schedule[invalid_move.tick - 1].contains(&index));
+            }
+        }
+    }
+}
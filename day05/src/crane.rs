@@ -0,0 +1,249 @@
+//! Crane models, which differ in how they lift crates to carry out an instruction.
+use std::num::NonZeroUsize;
+
+use crate::stacks::{Crate, StackError, Stacks};
+use crate::Instruction;
+
+/// A single lift made by a crane, taking crates from the top of the source stack and putting
+/// them on top of the destination stack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Lift {
+    pub n_crates: usize,
+    /// Whether the crates are put down in the reverse of the order they were stacked in.
+    pub reversed: bool,
+}
+
+/// A model of crane, which carries out each instruction as a sequence of lifts.
+pub trait Crane {
+    /// The name of the crane model.
+    fn name(&self) -> String;
+
+    /// The lifts the crane makes to carry out the instruction.
+    fn lifts(&mut self, instruction: &Instruction) -> Vec<Lift>;
+
+    /// Apply the instruction to the stacks. The stacks are left unchanged if the instruction
+    /// is not possible.
+    fn apply(&mut self, instruction: &Instruction, stacks: &mut Stacks) -> Result<(), StackError> {
+        stacks.check_move(instruction.n_crates, instruction.move_from, instruction.move_to)?;
+        for lift in self.lifts(instruction) {
+            make_lift(&lift, instruction, stacks)?;
+        }
+        Ok(())
+    }
+}
+
+/// Move the crates for one lift of an instruction.
+fn make_lift(
+    lift: &Lift,
+    instruction: &Instruction,
+    stacks: &mut Stacks,
+) -> Result<(), StackError> {
+    if lift.reversed {
+        stacks.move_crates_reversed(lift.n_crates, instruction.move_from, instruction.move_to)
+    } else {
+        stacks.move_crates(lift.n_crates, instruction.move_from, instruction.move_to)
+    }
+}
+
+/// The CrateMover 9000, which lifts one crate at a time.
+#[derive(Clone, Copy, Debug)]
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        "CrateMover 9000".to_string()
+    }
+
+    fn lifts(&mut self, instruction: &Instruction) -> Vec<Lift> {
+        vec![Lift { n_crates: 1, reversed: false }; instruction.n_crates]
+    }
+
+    /// Apply the instruction, moving the crates in bulk rather than one lift at a time.
+    fn apply(&mut self, instruction: &Instruction, stacks: &mut Stacks) -> Result<(), StackError> {
+        instruction.apply(stacks)
+    }
+}
+
+/// The CrateMover 9001, which lifts all of the crates at once.
+#[derive(Clone, Copy, Debug)]
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        "CrateMover 9001".to_string()
+    }
+
+    fn lifts(&mut self, instruction: &Instruction) -> Vec<Lift> {
+        vec![Lift { n_crates: instruction.n_crates, reversed: false }]
+    }
+
+    fn apply(&mut self, instruction: &Instruction, stacks: &mut Stacks) -> Result<(), StackError> {
+        instruction.apply_move_multi(stacks)
+    }
+}
+
+/// A crane which can lift at most `capacity` crates at a time, keeping their order.
+#[derive(Clone, Copy, Debug)]
+pub struct LimitedCrane {
+    pub capacity: NonZeroUsize,
+}
+
+impl Crane for LimitedCrane {
+    fn name(&self) -> String {
+        format!("limited crane (capacity {})", self.capacity)
+    }
+
+    fn lifts(&mut self, instruction: &Instruction) -> Vec<Lift> {
+        let capacity = self.capacity.get();
+        let mut lifts =
+            vec![Lift { n_crates: capacity, reversed: false }; instruction.n_crates / capacity];
+        if !instruction.n_crates.is_multiple_of(capacity) {
+            lifts.push(Lift { n_crates: instruction.n_crates % capacity, reversed: false });
+        }
+        lifts
+    }
+}
+
+/// A crane which lifts all of the crates at once, but whose grip flips every other lift so that
+/// the crates are put down in reverse order.
+#[derive(Clone, Debug, Default)]
+pub struct AlternatingCrane {
+    n_lifts: usize,
+}
+
+impl Crane for AlternatingCrane {
+    fn name(&self) -> String {
+        "alternating crane".to_string()
+    }
+
+    fn lifts(&mut self, instruction: &Instruction) -> Vec<Lift> {
+        let reversed = !self.n_lifts.is_multiple_of(2);
+        self.n_lifts += 1;
+        vec![Lift { n_crates: instruction.n_crates, reversed }]
+    }
+}
+
+/// A lift made by a crane, along with the instruction it was made for and the crates it moved
+/// (from bottom to top, as they were stacked before the lift).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedLift {
+    pub instruction: Instruction,
+    pub lift: Lift,
+    pub crates: Vec<Crate>,
+}
+
+/// A crane which records each lift made by another crane model.
+pub struct RecordingCrane<C: Crane> {
+    pub crane: C,
+    pub lifts: Vec<RecordedLift>,
+}
+
+impl<C: Crane> RecordingCrane<C> {
+    /// Record the lifts made by a crane.
+    pub fn new(crane: C) -> RecordingCrane<C> {
+        RecordingCrane { crane, lifts: Vec::new() }
+    }
+}
+
+impl<C: Crane> Crane for RecordingCrane<C> {
+    fn name(&self) -> String {
+        format!("recording {}", self.crane.name())
+    }
+
+    fn lifts(&mut self, instruction: &Instruction) -> Vec<Lift> {
+        self.crane.lifts(instruction)
+    }
+
+    fn apply(&mut self, instruction: &Instruction, stacks: &mut Stacks) -> Result<(), StackError> {
+        stacks.check_move(instruction.n_crates, instruction.move_from, instruction.move_to)?;
+        for lift in self.lifts(instruction) {
+            let source = stacks.stack(instruction.move_from)?;
+            let crates = source[source.len() - lift.n_crates..].to_vec();
+            make_lift(&lift, instruction, stacks)?;
+            self.lifts.push(RecordedLift { instruction: *instruction, lift, crates });
+        }
+        Ok(())
+    }
+}
+
+/// The CrateMover 9001 if `multi` is set, or otherwise the CrateMover 9000.
+pub fn crate_mover(multi: bool) -> Box<dyn Crane> {
+    if multi {
+        Box::new(CrateMover9001)
+    } else {
+        Box::new(CrateMover9000)
+    }
+}
+
+/// Create a crane model from its name: `9000`, `9001`, `limited:<capacity>`, `alternating`, or
+/// `recording:<name>` to record the lifts of another model.
+pub fn crane_from_name(name: &str) -> Option<Box<dyn Crane>> {
+    match name.split_once(':') {
+        Some(("limited", capacity)) => {
+            let capacity = capacity.parse::<NonZeroUsize>().ok()?;
+            Some(Box::new(LimitedCrane { capacity }))
+        }
+        Some(("recording", inner)) => {
+            let inner = crane_from_name(inner)?;
+            Some(Box::new(RecordingCrane::new(inner)))
+        }
+        Some(_) => None,
+        None => match name {
+            "9000" => Some(Box::new(CrateMover9000)),
+            "9001" => Some(Box::new(CrateMover9001)),
+            "alternating" => Some(Box::new(AlternatingCrane::default())),
+            _ => None,
+        },
+    }
+}
+
+impl Crane for Box<dyn Crane> {
+    fn name(&self) -> String {
+        self.as_ref().name()
+    }
+
+    fn lifts(&mut self, instruction: &Instruction) -> Vec<Lift> {
+        self.as_mut().lifts(instruction)
+    }
+
+    fn apply(&mut self, instruction: &Instruction, stacks: &mut Stacks) -> Result<(), StackError> {
+        self.as_mut().apply(instruction, stacks)
+    }
+}
+
+/// Tests for the crane models.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_stacks_instructions, EXAMPLE};
+
+    fn run(crane: &mut dyn Crane) -> String {
+        let (mut stacks, instructions) = parse_stacks_instructions(EXAMPLE).unwrap();
+        for instruction in &instructions {
+            crane.apply(instruction, &mut stacks).unwrap();
+        }
+        stacks.tops().unwrap().iter().map(|some_crate| some_crate.label()).collect()
+    }
+
+    /// Test that each crane model gives the expected top crates for the example.
+    #[test]
+    fn test_crane_models() {
+        assert_eq!(run(&mut CrateMover9000), "CMZ");
+        assert_eq!(run(&mut CrateMover9001), "MCD");
+        assert_eq!(run(&mut LimitedCrane { capacity: NonZeroUsize::new(1).unwrap() }), "CMZ");
+        assert_eq!(run(&mut LimitedCrane { capacity: NonZeroUsize::new(2).unwrap() }), "MCZ");
+        assert_eq!(run(&mut AlternatingCrane::default()), "MCZ");
+    }
+
+    /// Test that the recording crane records each lift and the crates it moved.
+    #[test]
+    fn test_recording_crane() {
+        let mut crane =
+            RecordingCrane::new(LimitedCrane { capacity: NonZeroUsize::new(2).unwrap() });
+        assert_eq!(run(&mut crane), "MCZ");
+
+        let lifted: Vec<Vec<&str>> =
+            crane.lifts.iter().map(|lift| lift.crates.iter().map(Crate::label).collect()).collect();
+        assert_eq!(lifted, vec![vec!["D"], vec!["N", "D"], vec!["Z"], vec!["M", "C"], vec!["C"]]);
+    }
+}
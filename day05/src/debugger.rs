@@ -0,0 +1,263 @@
+//! An interactive debugger for stepping forwards and backwards through the crane instructions.
+use std::io::{self, BufRead, Write};
+
+use crate::crane::Crane;
+use crate::drawing::render_drawing;
+use crate::stacks::{StackError, Stacks};
+use crate::Instruction;
+
+/// The state of a debugging session: the stacks after some number of the instructions.
+///
+/// The stacks are kept for every position which has been reached, so that stepping back restores
+/// them rather than asking the crane to undo its lifts, and the crane only ever carries out each
+/// instruction once, in order.
+pub struct Debugger<'a, C: Crane> {
+    instructions: &'a [Instruction],
+    crane: C,
+    /// The stacks after each number of instructions which has been reached, starting from `first`.
+    snapshots: Vec<Stacks>,
+    first: usize,
+    position: usize,
+}
+
+/// A failure to apply an instruction while stepping.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepError {
+    /// The (zero-based) index of the instruction which could not be applied.
+    pub index: usize,
+    pub instruction: Instruction,
+    pub error: StackError,
+}
+
+impl<'a, C: Crane> Debugger<'a, C> {
+    /// Start a session before the first instruction, applying the instructions with the crane.
+    pub fn new(stacks: Stacks, instructions: &'a [Instruction], crane: C) -> Debugger<'a, C> {
+        Debugger { instructions, crane, snapshots: vec![stacks], first: 0, position: 0 }
+    }
+
+    /// Start a session after the last instruction, from the final stacks.
+    ///
+    /// There are no earlier stacks to restore, so stepping back applies the inverse of each
+    /// instruction with the crane instead, and stepping forwards from there applies the
+    /// instructions again. This only undoes the instructions for cranes which move the crates
+    /// like the CrateMover 9000 or 9001.
+    pub fn at_end(stacks: Stacks, instructions: &'a [Instruction], crane: C) -> Debugger<'a, C> {
+        let position = instructions.len();
+        Debugger { instructions, crane, snapshots: vec![stacks], first: position, position }
+    }
+
+    /// The number of instructions which have been applied.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The current stacks.
+    pub fn stacks(&self) -> &Stacks {
+        &self.snapshots[self.position - self.first]
+    }
+
+    /// Apply the next instruction, returning whether there was one to apply.
+    pub fn step(&mut self) -> Result<bool, StepError> {
+        let Some(instruction) = self.instructions.get(self.position) else {
+            return Ok(false);
+        };
+        if self.position + 1 - self.first == self.snapshots.len() {
+            let mut stacks = self.stacks().clone();
+            self.crane.apply(instruction, &mut stacks).map_err(|error| StepError {
+                index: self.position,
+                instruction: *instruction,
+                error,
+            })?;
+            self.snapshots.push(stacks);
+        }
+        self.position += 1;
+        Ok(true)
+    }
+
+    /// Undo the last instruction by restoring the stacks from before it, returning whether there
+    /// was one to undo.
+    pub fn back(&mut self) -> Result<bool, StepError> {
+        let Some(index) = self.position.checked_sub(1) else {
+            return Ok(false);
+        };
+        if self.position == self.first {
+            let inverse = self.instructions[index].inverse();
+            let mut stacks = self.stacks().clone();
+            self.crane.apply(&inverse, &mut stacks).map_err(|error| StepError {
+                index,
+                instruction: inverse,
+                error,
+            })?;
+            self.snapshots = vec![stacks];
+            self.first = index;
+        }
+        self.position = index;
+        Ok(true)
+    }
+
+    /// Step forwards or backwards until `position` instructions have been applied.
+    pub fn jump(&mut self, position: usize) -> Result<(), StepError> {
+        let position = position.min(self.instructions.len());
+        while self.position < position {
+            self.step()?;
+        }
+        while self.position > position {
+            self.back()?;
+        }
+        Ok(())
+    }
+
+    /// Step forwards until the condition holds for the stacks, returning whether it was met
+    /// before the instructions ran out.
+    pub fn run_until<F: Fn(&Stacks) -> bool>(&mut self, condition: F) -> Result<bool, StepError> {
+        while self.step()? {
+            if condition(self.stacks()) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Run a debugging session, reading one command per line and writing the results.
+///
+/// Supported commands are:
+///  - `step [n]`: apply the next n (default one) instructions.
+///  - `back [n]`: undo the last n (default one) instructions.
+///  - `jump <n>`: step forwards or backwards until n instructions have been applied.
+///  - `until-empty`: step forwards until any stack is empty.
+///  - `until-top <label>`: step forwards until the crate with the label is on top of a stack.
+///  - `show`: draw the current stacks.
+pub fn run_session<C: Crane, R: BufRead, W: Write>(
+    debugger: &mut Debugger<C>,
+    commands: R,
+    mut output: W,
+) -> io::Result<()> {
+    for command in commands.lines() {
+        let command = command?;
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let count = |default: usize| words.get(1).map_or(Some(default), |word| word.parse().ok());
+
+        let result = match (words.first().copied(), count(1)) {
+            (None, _) => continue,
+            (Some("show"), _) => {
+                write!(output, "{}", render_drawing(debugger.stacks()))?;
+                Ok(())
+            }
+            (Some("step"), Some(n_steps)) => {
+                (0..n_steps).try_for_each(|_| debugger.step().map(|_| ()))
+            }
+            (Some("back"), Some(n_steps)) => {
+                (0..n_steps).try_for_each(|_| debugger.back().map(|_| ()))
+            }
+            (Some("jump"), Some(position)) if words.len() == 2 => debugger.jump(position),
+            (Some("until-empty"), _) => debugger
+                .run_until(|stacks| stacks.ids().any(|id| stacks.height(id) == Ok(0)))
+                .map(|_| ()),
+            (Some("until-top"), _) if words.len() == 2 => debugger
+                .run_until(|stacks| {
+                    stacks.ids().any(|id| stacks.top(id).is_ok_and(|top| top.label() == words[1]))
+                })
+                .map(|_| ()),
+            _ => {
+                writeln!(output, "unknown command: {}", command)?;
+                continue;
+            }
+        };
+
+        if let Err(failure) = result {
+            writeln!(
+                output,
+                "error at instruction {} ({}): {}",
+                failure.index + 1,
+                failure.instruction,
+                failure.error
+            )?;
+        }
+        if words[0] != "show" {
+            writeln!(
+                output,
+                "at instruction {} of {}",
+                debugger.position(),
+                debugger.instructions.len()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Tests for the instruction debugger.
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+    use crate::crane::{AlternatingCrane, CrateMover9000, LimitedCrane};
+    use crate::{parse_stacks_instructions, EXAMPLE};
+
+    /// Test a scripted session against the puzzle's example.
+    #[test]
+    fn test_scripted_session() {
+        let example = format!("{}move 5 from 2 to 1\n", EXAMPLE);
+        let (stacks, instructions) = parse_stacks_instructions(&example).unwrap();
+        let mut debugger = Debugger::new(stacks, &instructions, CrateMover9000);
+
+        let commands = "step 2\nshow\nback\njump 0\nshow\nuntil-empty\nuntil-top Z\nstep 3\nfly\n";
+        let mut output: Vec<u8> = Vec::new();
+        run_session(&mut debugger, commands.as_bytes(), &mut output).unwrap();
+
+        let expected = "\
+at instruction 2 of 5
+        [Z]
+        [N]
+    [C] [D]
+    [M] [P]
+ 1   2   3
+at instruction 1 of 5
+at instruction 0 of 5
+    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+at instruction 2 of 5
+at instruction 3 of 5
+error at instruction 5 (move 5 from 2 to 1): cannot take 5 crates from stack 2, which holds 1
+at instruction 4 of 5
+unknown command: fly
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    /// Test that stepping back restores the stacks for cranes which cannot undo their lifts, and
+    /// that stepping forwards again gives the same stacks.
+    #[test]
+    fn test_back_without_inverse() {
+        let (start, instructions) = parse_stacks_instructions(EXAMPLE).unwrap();
+        let make_crane = |alternating: bool| -> Box<dyn Crane> {
+            match alternating {
+                false => Box::new(LimitedCrane { capacity: NonZeroUsize::new(2).unwrap() }),
+                true => Box::new(AlternatingCrane::default()),
+            }
+        };
+
+        for alternating in [false, true] {
+            let mut replay = make_crane(alternating);
+            let mut expected = vec![start.clone()];
+            for instruction in &instructions {
+                let mut stacks = expected.last().unwrap().clone();
+                replay.apply(instruction, &mut stacks).unwrap();
+                expected.push(stacks);
+            }
+
+            let mut debugger = Debugger::new(start.clone(), &instructions, make_crane(alternating));
+            debugger.jump(instructions.len()).unwrap();
+            for position in (0..instructions.len()).rev() {
+                assert!(debugger.back().unwrap());
+                assert_eq!(debugger.stacks(), &expected[position]);
+            }
+            assert!(!debugger.back().unwrap());
+            debugger.jump(instructions.len()).unwrap();
+            assert_eq!(debugger.stacks(), &expected[instructions.len()]);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Parsing and rendering of the puzzle's drawing of the stacks of crates.
+use crate::stacks::{Crate, StackId, Stacks};
+
+/// A run of non-space characters in a line of the drawing, with its (inclusive) column span.
+struct Token<'a> {
+    first_column: usize,
+    last_column: usize,
+    text: &'a str,
+}
+
+impl Token<'_> {
+    /// Whether the token shares any columns with the other.
+    fn overlaps(&self, other: &Token) -> bool {
+        self.first_column <= other.last_column && self.last_column >= other.first_column
+    }
+}
+
+/// Split a line of the drawing into tokens. Crates are delimited by their brackets (so that
+/// labels may contain spaces); anything else is delimited by spaces.
+fn tokenise(line: &str) -> Result<Vec<Token<'_>>, &'static str> {
+    let characters: Vec<(usize, char)> = line.char_indices().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+
+    let mut column = 0;
+    while column < characters.len() {
+        let (offset, character) = characters[column];
+        if character == ' ' {
+            column += 1;
+            continue;
+        }
+
+        let last_column = if character == '[' {
+            (column..characters.len())
+                .find(|end_column| characters[*end_column].1 == ']')
+                .ok_or("Expected a crate to end with ']'")?
+        } else {
+            (column..characters.len())
+                .take_while(|end_column| characters[*end_column].1 != ' ')
+                .last()
+                .unwrap_or(column)
+        };
+
+        let end_offset = characters.get(last_column + 1).map_or(line.len(), |(offset, _)| *offset);
+        tokens.push(Token { first_column: column, last_column, text: &line[offset..end_offset] });
+        column = last_column + 1;
+    }
+    Ok(tokens)
+}
+
+/// Parse the drawing of the stacks, with the stack labels on the last line.
+///
+/// The columns of the stacks are found from the positions of their labels, so labels may be
+/// any width. Each crate belongs to the stack whose label sits underneath it, and crates may
+/// have labels of any width (e.g. `[AB]`). Lines may have their trailing spaces trimmed.
+pub fn parse_drawing(lines: &[&str]) -> Result<Stacks, &'static str> {
+    let (label_line, crate_lines) = lines.split_last().ok_or("Expected a row of stack numbers")?;
+
+    let labels = tokenise(label_line)?;
+    let ids = labels
+        .iter()
+        .map(|label| label.text.parse::<StackId>().map_err(|_| "Expected a stack number"))
+        .collect::<Result<Vec<StackId>, &'static str>>()?;
+    let mut stacks = Stacks::new(ids.iter().copied());
+    if stacks.ids().count() != ids.len() {
+        return Err("Expected each stack number to be unique");
+    }
+
+    for line in crate_lines.iter().rev() {
+        let mut filled: Vec<bool> = vec![false; labels.len()];
+
+        for token in tokenise(line)? {
+            let some_crate = Crate::parse(token.text).ok_or("Expected a crate like '[Z]'")?;
+            let position = labels
+                .iter()
+                .position(|label| token.overlaps(label))
+                .ok_or("Expected each crate to sit above a stack number")?;
+            if filled[position] {
+                return Err("Expected at most one crate above each stack number per row");
+            }
+            filled[position] = true;
+            stacks.push(ids[position], some_crate).map_err(|_| "Unknown stack")?;
+        }
+    }
+    Ok(stacks)
+}
+
+/// Render the stacks in the drawing format read by `parse_drawing`, with the stack labels
+/// underneath. Each column is wide enough for the widest crate or label in the drawing, and
+/// labels are centred under their column. Trailing spaces are trimmed from each line.
+pub fn render_drawing(stacks: &Stacks) -> String {
+    let ids: Vec<StackId> = stacks.ids().collect();
+    let columns: Vec<&[Crate]> = ids.iter().filter_map(|id| stacks.stack(*id).ok()).collect();
+
+    let widest_crate = columns
+        .iter()
+        .flat_map(|column| column.iter())
+        .map(|some_crate| some_crate.label().chars().count() + 2);
+    let widest_label = ids.iter().map(|id| id.to_string().len());
+    let width = widest_crate.chain(widest_label).max().unwrap_or(0).max(3);
+
+    let mut lines: Vec<String> = Vec::new();
+    let max_height = columns.iter().map(|column| column.len()).max().unwrap_or(0);
+    for level in (0..max_height).rev() {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|column| match column.get(level) {
+                Some(some_crate) => {
+                    format!("{:^width$}", format!("[{}]", some_crate), width = width)
+                }
+                None => " ".repeat(width),
+            })
+            .collect();
+        lines.push(cells.join(" "));
+    }
+
+    let labels: Vec<String> = ids
+        .iter()
+        .map(|id| {
+            let label = id.to_string();
+            let left = (width - label.len()).div_ceil(2);
+            format!("{}{}{}", " ".repeat(left), label, " ".repeat(width - label.len() - left))
+        })
+        .collect();
+    lines.push(labels.join(" "));
+
+    lines.iter().map(|line| format!("{}\n", line.trim_end())).collect()
+}
+
+/// Tests for the drawing parser and renderer.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn labels(stacks: &Stacks, id: StackId) -> Vec<&str> {
+        stacks.stack(id).unwrap().iter().map(Crate::label).collect()
+    }
+
+    /// Test a drawing with ten stacks, multi-letter crates and trimmed lines.
+    #[test]
+    fn test_wide_drawing() {
+        let drawing = [
+            "                                    [X]",
+            "[AB]                        [Q]     [Y]",
+            "[C]     [D]                 [R] [S] [Z]",
+            " 1   2   3   4   5   6   7   8   9  10",
+        ];
+        let stacks = parse_drawing(&drawing).unwrap();
+
+        assert_eq!(stacks.ids().collect::<Vec<StackId>>(), (1..=10).collect::<Vec<StackId>>());
+        assert_eq!(labels(&stacks, 1), vec!["C", "AB"]);
+        assert_eq!(labels(&stacks, 2), Vec::<&str>::new());
+        assert_eq!(labels(&stacks, 3), vec!["D"]);
+        assert_eq!(labels(&stacks, 8), vec!["R", "Q"]);
+        assert_eq!(labels(&stacks, 9), vec!["S"]);
+        assert_eq!(labels(&stacks, 10), vec!["Z", "Y", "X"]);
+    }
+
+    /// Test that crates which are not above a label are rejected.
+    #[test]
+    fn test_misaligned_drawing() {
+        assert!(parse_drawing(&["    [A]", " 1   2"]).is_ok());
+        assert!(parse_drawing(&["      [A]", " 1   2"]).is_err());
+        assert!(parse_drawing(&["[A] [B]", " 1   1"]).is_err());
+    }
+
+    /// Test that rendering the parsed input gives back its drawing, byte for byte.
+    #[test]
+    fn test_render_round_trip() {
+        let input = include_str!("../input_01.txt");
+        let header_end = input.find("\n\n").unwrap() + 1;
+        let header = &input[..header_end];
+
+        let lines: Vec<&str> = header.lines().collect();
+        let stacks = parse_drawing(&lines).unwrap();
+        assert_eq!(render_drawing(&stacks), header);
+    }
+
+    /// Test that rendering a wide drawing can be parsed back into the same stacks.
+    #[test]
+    fn test_render_wide_drawing() {
+        let mut stacks = Stacks::new(9..=11);
+        stacks.push(9, Crate::new("A")).unwrap();
+        stacks.push(9, Crate::new("BCD")).unwrap();
+        stacks.push(11, Crate::new("E")).unwrap();
+
+        let rendered = render_drawing(&stacks);
+        assert_eq!(rendered, "[BCD]\n [A]         [E]\n  9     10    11\n");
+        assert_eq!(parse_drawing(&rendered.lines().collect::<Vec<&str>>()), Ok(stacks));
+    }
+}
@@ -0,0 +1,156 @@
+//! Validated execution of a listing of instructions, with errors which say where it went wrong.
+use std::fmt;
+
+use crate::crane::Crane;
+use crate::stacks::{StackError, StackId, Stacks};
+use crate::Instruction;
+
+/// How to handle instructions which move more crates than the source stack holds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Policy {
+    /// Stop with an error.
+    #[default]
+    Strict,
+    /// Move only the crates which the stack holds.
+    Lenient,
+}
+
+/// A failure to apply an instruction from the listing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionError {
+    /// The (zero-based) index of the instruction.
+    pub index: usize,
+    /// The (one-based) line of the input which held the instruction.
+    pub line: usize,
+    pub instruction: Instruction,
+    pub error: StackError,
+    /// The height of each stack when the instruction was applied.
+    pub heights: Vec<(StackId, usize)>,
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let heights: Vec<String> =
+            self.heights.iter().map(|(id, height)| format!("{}:{}", id, height)).collect();
+        write!(
+            formatter,
+            "instruction {} (line {}: {}): {} (stack heights {})",
+            self.index + 1,
+            self.line,
+            self.instruction,
+            self.error,
+            heights.join(" ")
+        )
+    }
+}
+
+impl std::error::Error for ExecutionError {}
+
+/// The stacks after executing a listing, and the instructions which had to be clamped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Execution {
+    pub stacks: Stacks,
+    /// The (zero-based) indices of the instructions which moved fewer crates than requested.
+    pub clamped: Vec<usize>,
+}
+
+impl Execution {
+    /// The labels of the stacks left without any crates.
+    pub fn empty_stacks(&self) -> Vec<StackId> {
+        self.stacks.ids().filter(|id| self.stacks.height(*id) == Ok(0)).collect()
+    }
+}
+
+/// Apply one instruction from a listing with the crane, returning whether it had to be
+/// clamped. The stacks are left unchanged if it could not be applied.
+pub fn execute_one<C: Crane>(
+    stacks: &mut Stacks,
+    index: usize,
+    (line, instruction): (usize, Instruction),
+    crane: &mut C,
+    policy: Policy,
+) -> Result<bool, ExecutionError> {
+    let mut clamped = instruction;
+    if policy == Policy::Lenient {
+        if let Ok(available) = stacks.height(instruction.move_from) {
+            clamped.n_crates = instruction.n_crates.min(available);
+        }
+    }
+
+    crane.apply(&clamped, stacks).map_err(|error| ExecutionError {
+        index,
+        line,
+        instruction,
+        error,
+        heights: stacks.ids().map(|id| (id, stacks.height(id).unwrap_or_default())).collect(),
+    })?;
+    Ok(clamped != instruction)
+}
+
+/// Apply each instruction from a listing (as read by `parse_stacks_listing`) to the stacks in
+/// turn, stopping at the first which cannot be applied.
+pub fn execute<C: Crane>(
+    stacks: Stacks,
+    listing: &[(usize, Instruction)],
+    crane: C,
+    policy: Policy,
+) -> Result<Execution, ExecutionError> {
+    execute_traced(stacks, listing, crane, policy, |_, _| {})
+}
+
+/// Execute a listing as for `execute`, calling `on_step` with each instruction and the stacks
+/// after it was applied.
+pub fn execute_traced<C: Crane, F: FnMut(&Instruction, &Stacks)>(
+    stacks: Stacks,
+    listing: &[(usize, Instruction)],
+    mut crane: C,
+    policy: Policy,
+    mut on_step: F,
+) -> Result<Execution, ExecutionError> {
+    let mut execution = Execution { stacks, clamped: Vec::new() };
+    for (index, entry) in listing.iter().enumerate() {
+        if execute_one(&mut execution.stacks, index, *entry, &mut crane, policy)? {
+            execution.clamped.push(index);
+        }
+        on_step(&entry.1, &execution.stacks);
+    }
+    Ok(execution)
+}
+
+/// Tests for executing listings of instructions.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::{parse_stacks_listing, EXAMPLE};
+
+    /// Test the errors from strict execution, and clamping with lenient execution.
+    #[test]
+    fn test_execute() {
+        let example = format!("{}\nmove 4 from 2 to 1\nmove 1 from 4 to 2\n", EXAMPLE);
+        let (stacks, listing) = parse_stacks_listing(&example).unwrap();
+        let lines: Vec<usize> = listing.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![6, 7, 8, 9, 11, 12]);
+
+        let error = execute(stacks.clone(), &listing, CrateMover9000, Policy::Strict);
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "instruction 5 (line 11: move 4 from 2 to 1): cannot take 4 crates from stack 2, which \
+             holds 1 (stack heights 1:1 2:1 3:4)"
+        );
+
+        let error = execute(stacks.clone(), &listing, CrateMover9000, Policy::Lenient);
+        let error = error.unwrap_err();
+        assert_eq!((error.index, error.line, error.error), (5, 12, StackError::UnknownStack(4)));
+
+        let mut heights: Vec<usize> = Vec::new();
+        let on_step = |_: &Instruction, stacks: &Stacks| heights.push(stacks.height(2).unwrap());
+        let execution =
+            execute_traced(stacks, &listing[..5], CrateMover9001, Policy::Lenient, on_step);
+        let execution = execution.unwrap();
+        assert_eq!(heights, vec![2, 2, 0, 1, 0]);
+        assert_eq!(execution.clamped, vec![4]);
+        assert_eq!(execution.empty_stacks(), vec![2]);
+        assert_eq!(execution.stacks.height(1), Ok(2));
+    }
+}
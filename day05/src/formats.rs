@@ -0,0 +1,277 @@
+//! Reading and writing the stacks and instructions in each of the supported formats.
+use crate::drawing::render_drawing;
+use crate::json::{self, array, quote, Value};
+use crate::stacks::{Crate, StackId, Stacks};
+use crate::{parse_instruction, parse_stacks_listing, Instruction, Listing};
+
+/// A format for the stacks and instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// The puzzle's drawing of the stacks, then `move 3 from 1 to 2` instructions.
+    Drawing,
+    /// A line per stack like `1: ZN` (bottom to top, with wide crates bracketed like `[AB]` and
+    /// any `]` or `\` in a bracketed label escaped with a `\`), then shorthand instructions like
+    /// `3 1->2`.
+    Compact,
+    /// A JSON object with `stacks` (each an `id` and its `crates`, bottom to top) and
+    /// `instructions` (each with `move`, `from` and `to`).
+    Json,
+}
+
+impl Format {
+    /// The format with the given name: `drawing`, `compact` or `json`.
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "drawing" => Some(Format::Drawing),
+            "compact" => Some(Format::Compact),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    /// Guess the format of some input from how it starts.
+    pub fn detect(input_string: &str) -> Format {
+        let first_line = input_string.lines().next().unwrap_or_default();
+        if input_string.trim_start().starts_with('{') {
+            Format::Json
+        } else if first_line
+            .split_once(':')
+            .is_some_and(|(id, _)| id.trim().parse::<StackId>().is_ok())
+        {
+            Format::Compact
+        } else {
+            Format::Drawing
+        }
+    }
+}
+
+/// Parse the compact form of the stacks, one line per stack.
+pub fn parse_compact(lines: &[&str]) -> Result<Stacks, &'static str> {
+    let mut columns: Vec<(StackId, Vec<Crate>)> = Vec::new();
+    for line in lines {
+        let (id, crates) = line.split_once(':').ok_or("Expected a stack like '1: ZN'")?;
+        let id = id.trim().parse::<StackId>().map_err(|_| "Expected a stack number")?;
+
+        let mut column: Vec<Crate> = Vec::new();
+        let mut characters = crates.trim().chars();
+        while let Some(character) = characters.next() {
+            if character == '[' {
+                let mut label = String::new();
+                loop {
+                    match characters.next().ok_or("Expected a ']' after the crate label")? {
+                        ']' => break,
+                        '\\' => {
+                            label.push(characters.next().ok_or("Expected an escaped character")?)
+                        }
+                        next => label.push(next),
+                    }
+                }
+                if label.is_empty() {
+                    return Err("Expected a crate label");
+                }
+                column.push(Crate::new(&label));
+            } else {
+                column.push(Crate::new(&character.to_string()));
+            }
+        }
+        columns.push((id, column));
+    }
+
+    let mut stacks = Stacks::new(columns.iter().map(|(id, _)| *id));
+    if stacks.ids().count() != columns.len() {
+        return Err("Expected each stack number to be unique");
+    }
+    for (id, column) in columns {
+        for some_crate in column {
+            stacks.push(id, some_crate).map_err(|_| "Unknown stack")?;
+        }
+    }
+    Ok(stacks)
+}
+
+/// Render the compact form of the stacks, one line per stack.
+pub fn render_compact(stacks: &Stacks) -> String {
+    let mut lines = String::new();
+    for id in stacks.ids() {
+        let crates: String = stacks
+            .stack(id)
+            .unwrap_or_default()
+            .iter()
+            .map(|some_crate| match some_crate.label() {
+                label if label.chars().count() == 1 && label != "[" && label != " " => {
+                    label.to_string()
+                }
+                label => format!("[{}]", label.replace('\\', "\\\\").replace(']', "\\]")),
+            })
+            .collect();
+        lines.push_str(format!("{}: {}", id, crates).trim_end());
+        lines.push('\n');
+    }
+    lines
+}
+
+/// Read an integer member of a JSON object.
+fn json_integer<T: TryFrom<i64>>(value: &Value, key: &str) -> Result<T, &'static str> {
+    let number = value.get(key).and_then(Value::as_integer).ok_or("Expected an integer member")?;
+    T::try_from(number).map_err(|_| "Integer member is out of range")
+}
+
+/// Parse the JSON form of the stacks and instructions. The "line" of each instruction is its
+/// (one-based) position in the list of instructions.
+fn parse_json(input_string: &str) -> Result<(Stacks, Listing), &'static str> {
+    let document = json::parse(input_string)?;
+    let columns = document.get("stacks").and_then(Value::as_array).ok_or("Expected stacks")?;
+
+    let ids =
+        columns.iter().map(|column| json_integer(column, "id")).collect::<Result<Vec<_>, _>>()?;
+    let mut stacks = Stacks::new(ids.iter().copied());
+    if stacks.ids().count() != ids.len() {
+        return Err("Expected each stack number to be unique");
+    }
+    for (id, column) in ids.iter().zip(columns) {
+        let crates = column.get("crates").and_then(Value::as_array).ok_or("Expected crates")?;
+        for some_crate in crates {
+            let label = some_crate.as_str().filter(|label| !label.is_empty());
+            let some_crate = Crate::new(label.ok_or("Expected a crate label")?);
+            stacks.push(*id, some_crate).map_err(|_| "Unknown stack")?;
+        }
+    }
+
+    let instructions =
+        document.get("instructions").and_then(Value::as_array).ok_or("Expected instructions")?;
+    let mut listing: Listing = Vec::new();
+    for (index, instruction) in instructions.iter().enumerate() {
+        let n_crates = json_integer(instruction, "move")?;
+        let move_from = json_integer(instruction, "from")?;
+        let move_to = json_integer(instruction, "to")?;
+        listing.push((index + 1, Instruction { n_crates, move_from, move_to }));
+    }
+    Ok((stacks, listing))
+}
+
+/// Parse the stacks and instructions in the given format.
+pub fn parse_input(input_string: &str, format: Format) -> Result<(Stacks, Listing), &'static str> {
+    match format {
+        Format::Drawing => parse_stacks_listing(input_string),
+        Format::Json => parse_json(input_string),
+        Format::Compact => {
+            let mut lines = input_string.split('\n').enumerate();
+            let stack_lines: Vec<&str> =
+                lines.by_ref().map(|(_, line)| line).take_while(|line| !line.is_empty()).collect();
+            let stacks = parse_compact(&stack_lines)?;
+
+            let mut listing: Listing = Vec::new();
+            for (index, line) in lines.filter(|(_, line)| !line.is_empty()) {
+                listing.push((index + 1, parse_instruction(line)?));
+            }
+            Ok((stacks, listing))
+        }
+    }
+}
+
+/// Write the stacks and instructions in the given format.
+pub fn write_input(stacks: &Stacks, instructions: &[Instruction], format: Format) -> String {
+    match format {
+        Format::Drawing => {
+            let moves: String =
+                instructions.iter().map(|instruction| format!("{}\n", instruction)).collect();
+            format!("{}\n{}", render_drawing(stacks), moves)
+        }
+        Format::Compact => {
+            let moves: String = instructions
+                .iter()
+                .map(|instruction| {
+                    let Instruction { n_crates, move_from, move_to } = instruction;
+                    format!("{} {}->{}\n", n_crates, move_from, move_to)
+                })
+                .collect();
+            format!("{}\n{}", render_compact(stacks), moves)
+        }
+        Format::Json => {
+            let columns = array(stacks.ids().map(|id| {
+                let crates = stacks.stack(id).unwrap_or_default().iter();
+                let crates = array(crates.map(|some_crate| quote(some_crate.label())));
+                format!("{{\"id\":{},\"crates\":{}}}", id, crates)
+            }));
+            let moves = array(instructions.iter().map(|instruction| {
+                format!(
+                    "{{\"move\":{},\"from\":{},\"to\":{}}}",
+                    instruction.n_crates, instruction.move_from, instruction.move_to
+                )
+            }));
+            format!("{{\"stacks\":{},\"instructions\":{}}}\n", columns, moves)
+        }
+    }
+}
+
+/// Tests for the input formats.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn top_labels(stacks: &Stacks) -> String {
+        stacks.tops().unwrap().iter().map(|some_crate| some_crate.label()).collect()
+    }
+
+    /// Test that the compact form reads wide crates, empty stacks and shorthand instructions.
+    #[test]
+    fn test_compact() {
+        let input = "1: Z[AB]\n2:\n10: [[]C\n\n3 1->2\nmove 1 from 10 to 2\n";
+        assert_eq!(Format::detect(input), Format::Compact);
+
+        let (stacks, listing) = parse_input(input, Format::Compact).unwrap();
+        let labels: Vec<&str> = stacks.stack(1).unwrap().iter().map(Crate::label).collect();
+        assert_eq!(labels, vec!["Z", "AB"]);
+        assert_eq!(stacks.height(2), Ok(0));
+        assert_eq!(stacks.top(10).map(Crate::label), Ok("C"));
+        assert_eq!(listing[0], (5, Instruction { n_crates: 3, move_from: 1, move_to: 2 }));
+        assert_eq!(listing[1], (6, Instruction { n_crates: 1, move_from: 10, move_to: 2 }));
+    }
+
+    /// Test that bracketed labels are escaped so that they read back, and that unclosed
+    /// brackets are rejected.
+    #[test]
+    fn test_compact_escapes() {
+        let mut stacks = Stacks::new([1, 2]);
+        for label in ["a]b", "]", "[", " ", "\\", "x\\]", "[]"] {
+            stacks.push(1, Crate::new(label)).unwrap();
+        }
+        let rendered = render_compact(&stacks);
+        assert_eq!(rendered, "1: [a\\]b]][[][ ]\\[x\\\\\\]][[\\]]\n2:\n");
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(parse_compact(&lines), Ok(stacks));
+
+        assert_eq!(parse_compact(&["1: Z[AB"]), Err("Expected a ']' after the crate label"));
+        assert_eq!(parse_compact(&["1: []"]), Err("Expected a crate label"));
+    }
+
+    /// Test that the input gives the same results with both cranes, whatever format it was
+    /// converted into and read back from.
+    #[test]
+    fn test_round_trips() {
+        let input = include_str!("../input_01.txt");
+        let (stacks, listing) = parse_input(input, Format::detect(input)).unwrap();
+        let instructions: Vec<Instruction> =
+            listing.iter().map(|(_, instruction)| *instruction).collect();
+
+        for format in [Format::Drawing, Format::Compact, Format::Json] {
+            let written = write_input(&stacks, &instructions, format);
+            assert_eq!(Format::detect(&written), format);
+            let (read_stacks, read_listing) = parse_input(&written, format).unwrap();
+            let read_instructions: Vec<Instruction> =
+                read_listing.iter().map(|(_, instruction)| *instruction).collect();
+            assert_eq!((&read_stacks, &read_instructions), (&stacks, &instructions));
+
+            let (mut single, mut multi) = (read_stacks.clone(), read_stacks);
+            for instruction in &read_instructions {
+                instruction.apply(&mut single).unwrap();
+                instruction.apply_move_multi(&mut multi).unwrap();
+            }
+            assert_eq!(
+                (top_labels(&single).as_str(), top_labels(&multi).as_str()),
+                ("CNSZFDVLJ", "QNDWLMGNS")
+            );
+        }
+        assert_eq!(write_input(&stacks, &instructions, Format::Drawing), input);
+    }
+}
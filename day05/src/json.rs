@@ -0,0 +1,260 @@
+//! Just enough JSON to write the reports and read and write the stacks, without any
+//! dependencies.
+
+/// Quote a string as a JSON string literal.
+pub fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            character if character.is_control() => {
+                quoted.push_str(&format!("\\u{:04x}", u32::from(character)))
+            }
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Format the items as a JSON array, given their JSON forms.
+pub fn array<I: IntoIterator<Item = String>>(items: I) -> String {
+    format!("[{}]", items.into_iter().collect::<Vec<String>>().join(","))
+}
+
+/// A parsed JSON value. Numbers are limited to integers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    String(String),
+    Array(Vec<Value>),
+    /// An object's members, in the order they were written.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// The member of an object with the given key.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => {
+                members.iter().find(|(member, _)| member == key).map(|(_, value)| value)
+            }
+            _ => None,
+        }
+    }
+
+    /// The items of an array.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// The value of a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The value of an integer.
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(number) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+/// A recursive descent parser over the characters of a JSON document.
+struct Parser<'a> {
+    characters: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    /// Skip any whitespace, and peek at the next character.
+    fn peek(&mut self) -> Option<char> {
+        while self.characters.next_if(|character| character.is_whitespace()).is_some() {}
+        self.characters.peek().copied()
+    }
+
+    /// Consume the expected character, after any whitespace.
+    fn expect(&mut self, expected: char) -> Result<(), &'static str> {
+        match self.peek() {
+            Some(character) if character == expected => {
+                self.characters.next();
+                Ok(())
+            }
+            _ => Err("Unexpected character in JSON"),
+        }
+    }
+
+    /// Consume a literal keyword such as `true`.
+    fn keyword(&mut self, keyword: &str, value: Value) -> Result<Value, &'static str> {
+        for expected in keyword.chars() {
+            if self.characters.next() != Some(expected) {
+                return Err("Unexpected keyword in JSON");
+            }
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, &'static str> {
+        match self.peek().ok_or("Unexpected end of JSON")? {
+            'n' => self.keyword("null", Value::Null),
+            't' => self.keyword("true", Value::Bool(true)),
+            'f' => self.keyword("false", Value::Bool(false)),
+            '"' => self.string().map(Value::String),
+            '[' => {
+                self.characters.next();
+                let mut items: Vec<Value> = Vec::new();
+                if self.peek() == Some(']') {
+                    self.characters.next();
+                    return Ok(Value::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    match self.peek() {
+                        Some(',') => self.expect(',')?,
+                        _ => break,
+                    }
+                }
+                self.expect(']')?;
+                Ok(Value::Array(items))
+            }
+            '{' => {
+                self.characters.next();
+                let mut members: Vec<(String, Value)> = Vec::new();
+                if self.peek() == Some('}') {
+                    self.characters.next();
+                    return Ok(Value::Object(members));
+                }
+                loop {
+                    self.peek();
+                    let key = self.string()?;
+                    self.expect(':')?;
+                    members.push((key, self.value()?));
+                    match self.peek() {
+                        Some(',') => self.expect(',')?,
+                        _ => break,
+                    }
+                }
+                self.expect('}')?;
+                Ok(Value::Object(members))
+            }
+            _ => {
+                let mut digits = String::new();
+                while let Some(character) = self
+                    .characters
+                    .next_if(|character| *character == '-' || character.is_ascii_digit())
+                {
+                    digits.push(character);
+                }
+                digits.parse::<i64>().map(Value::Integer).map_err(|_| "Expected a JSON value")
+            }
+        }
+    }
+
+    /// Read the four hex digits of a `\u` escape.
+    fn code_unit(&mut self) -> Result<u32, &'static str> {
+        let code: String = self.characters.by_ref().take(4).collect();
+        if code.len() != 4 {
+            return Err("Bad JSON escape");
+        }
+        u32::from_str_radix(&code, 16).map_err(|_| "Bad JSON escape")
+    }
+
+    /// Read a `\u` escape, including the second half of a surrogate pair.
+    fn unicode_escape(&mut self) -> Result<char, &'static str> {
+        let mut code = self.code_unit()?;
+        if (0xD800..0xDC00).contains(&code) {
+            if self.characters.next() != Some('\\') || self.characters.next() != Some('u') {
+                return Err("Unpaired surrogate in JSON escape");
+            }
+            let low = self.code_unit()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err("Unpaired surrogate in JSON escape");
+            }
+            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+        }
+        char::from_u32(code).ok_or("Bad JSON escape")
+    }
+
+    /// Parse a string literal, which must start at the next character.
+    fn string(&mut self) -> Result<String, &'static str> {
+        if self.characters.next() != Some('"') {
+            return Err("Expected a JSON string");
+        }
+        let mut text = String::new();
+        loop {
+            match self.characters.next().ok_or("Unterminated JSON string")? {
+                '"' => return Ok(text),
+                '\\' => match self.characters.next().ok_or("Unterminated JSON string")? {
+                    'n' => text.push('\n'),
+                    't' => text.push('\t'),
+                    'r' => text.push('\r'),
+                    'b' => text.push('\u{8}'),
+                    'f' => text.push('\u{c}'),
+                    'u' => text.push(self.unicode_escape()?),
+                    escaped => text.push(escaped),
+                },
+                character => text.push(character),
+            }
+        }
+    }
+}
+
+/// Parse a JSON document.
+pub fn parse(text: &str) -> Result<Value, &'static str> {
+    let mut parser = Parser { characters: text.chars().peekable() };
+    let value = parser.value()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(_) => Err("Unexpected trailing characters after JSON"),
+    }
+}
+
+/// Tests for the JSON writer and parser.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Test that quoted strings and nested values parse back.
+    #[test]
+    fn test_parse() {
+        let text = format!(
+            "{{\"name\": {}, \"values\": [1, -2, true, null, {{}}], \"empty\": []}}",
+            quote("a \"quoted\"\\ line\n\u{1}")
+        );
+        let value = parse(&text).unwrap();
+        assert_eq!(value.get("name").and_then(Value::as_str), Some("a \"quoted\"\\ line\n\u{1}"));
+        assert_eq!(
+            value.get("values").and_then(Value::as_array).unwrap(),
+            &[
+                Value::Integer(1),
+                Value::Integer(-2),
+                Value::Bool(true),
+                Value::Null,
+                Value::Object(vec![])
+            ]
+        );
+        assert_eq!(value.get("empty"), Some(&Value::Array(vec![])));
+        assert!(parse("[1, 2").is_err());
+        assert!(parse("[1] 2").is_err());
+
+        let value = parse(r#"["\b\f", "\uD83D\uDE00 \u00e9"]"#).unwrap();
+        assert_eq!(
+            value.as_array().unwrap(),
+            &[Value::String("\u{8}\u{c}".to_string()), Value::String("\u{1F600} é".to_string())]
+        );
+        assert!(parse(r#""\uD83D""#).is_err());
+        assert!(parse(r#""\uDE00""#).is_err());
+    }
+}
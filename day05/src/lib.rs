@@ -0,0 +1,145 @@
+//! Crate stacks and crane instructions for the fifth advent of code problem.
+pub mod concurrent;
+pub mod crane;
+pub mod debugger;
+pub mod drawing;
+pub mod execute;
+pub mod formats;
+pub mod json;
+pub mod planner;
+pub mod provenance;
+pub mod reverse;
+pub mod stacks;
+
+use std::fmt;
+
+use drawing::parse_drawing;
+use stacks::{StackError, StackId, Stacks};
+
+/// Move instructions, each with the (one-based) line of the input it was read from.
+pub type Listing = Vec<(usize, Instruction)>;
+
+/// A move instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub n_crates: usize,
+    pub move_from: StackId,
+    pub move_to: StackId,
+}
+
+impl Instruction {
+    /// Apply the instruction with the CrateMover 9000, which moves crates one at a time. The
+    /// crates are moved in bulk, reversing their order.
+    pub fn apply(&self, stacks: &mut Stacks) -> Result<(), StackError> {
+        stacks.move_crates_reversed(self.n_crates, self.move_from, self.move_to)
+    }
+
+    /// Apply the instruction with the CrateMover 9001, which moves all the crates at once.
+    pub fn apply_move_multi(&self, stacks: &mut Stacks) -> Result<(), StackError> {
+        stacks.move_crates(self.n_crates, self.move_from, self.move_to)
+    }
+
+    /// The instruction which undoes this one, with either crane: the same number of crates,
+    /// moved back from the destination to the source.
+    pub fn inverse(&self) -> Instruction {
+        Instruction { n_crates: self.n_crates, move_from: self.move_to, move_to: self.move_from }
+    }
+}
+
+impl fmt::Display for Instruction {
+    /// Format the instruction as it appears in the input.
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "move {} from {} to {}", self.n_crates, self.move_from, self.move_to)
+    }
+}
+
+/// Parse an instruction, either as it appears in the puzzle (`move 3 from 1 to 2`) or in the
+/// shorthand `3 1->2`.
+pub fn parse_instruction(line: &str) -> Result<Instruction, &'static str> {
+    let line_split: Vec<&str> = line.split(' ').collect();
+    let (n_crates, move_from, move_to) = match line_split[..] {
+        ["move", n_crates, "from", move_from, "to", move_to] => (n_crates, move_from, move_to),
+        [n_crates, stacks] => {
+            let (move_from, move_to) =
+                stacks.split_once("->").ok_or("Expected a move like '1->2'")?;
+            (n_crates, move_from, move_to)
+        }
+        _ => return Err("Expected an instruction like 'move 1 from 2 to 3' or '1 2->3'"),
+    };
+
+    let n_crates = n_crates.parse::<usize>().map_err(|_| "Expected a crate count")?;
+    let move_from = move_from.parse::<StackId>().map_err(|_| "Expected a stack number")?;
+    let move_to = move_to.parse::<StackId>().map_err(|_| "Expected a stack number")?;
+    Ok(Instruction { n_crates, move_from, move_to })
+}
+
+/// Parse the drawing of the starting stacks, followed by a blank line and the move instructions.
+pub fn parse_stacks_instructions(
+    input_string: &str,
+) -> Result<(Stacks, Vec<Instruction>), &'static str> {
+    let (stacks, listing) = parse_stacks_listing(input_string)?;
+    Ok((stacks, listing.into_iter().map(|(_, instruction)| instruction).collect()))
+}
+
+/// Parse the drawing of the starting stacks and the move instructions, as for
+/// `parse_stacks_instructions`, keeping the (one-based) line number of each instruction.
+pub fn parse_stacks_listing(input_string: &str) -> Result<(Stacks, Listing), &'static str> {
+    let mut lines = input_string.split('\n').enumerate();
+    let mut stack_lines: Vec<&str> = Vec::new();
+
+    loop {
+        let (_, line) = lines.next().ok_or("Expected a blank line after the stacks")?;
+        if line.is_empty() {
+            break;
+        }
+        stack_lines.push(line);
+    }
+
+    let stacks = parse_drawing(&stack_lines)?;
+
+    let mut listing: Listing = Vec::new();
+    for (index, line) in lines {
+        if line.is_empty() {
+            continue;
+        }
+        listing.push((index + 1, parse_instruction(line)?));
+    }
+
+    Ok((stacks, listing))
+}
+
+/// The example drawing and instructions from the puzzle text.
+#[cfg(test)]
+pub(crate) const EXAMPLE: &str = "    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+";
+
+/// Tests for the crane instructions.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn top_labels(stacks: &Stacks) -> String {
+        stacks.tops().unwrap().iter().map(|some_crate| some_crate.label()).collect()
+    }
+
+    /// Test that both cranes give the puzzle's answers for the example.
+    #[test]
+    fn test_example() {
+        let (mut stacks, instructions) = parse_stacks_instructions(EXAMPLE).unwrap();
+        let mut multistacks = stacks.clone();
+        for instruction in &instructions {
+            instruction.apply(&mut stacks).unwrap();
+            instruction.apply_move_multi(&mut multistacks).unwrap();
+        }
+        assert_eq!(top_labels(&stacks), "CMZ");
+        assert_eq!(top_labels(&multistacks), "MCD");
+    }
+}
@@ -1,129 +1,317 @@
 //! Solution to the fifth advent of code problem.
-use std::collections::{HashMap, LinkedList};
+mod bench;
+
+use std::env;
 use std::fs::read_to_string;
+use std::io::{stdin, stdout, Read};
+use std::num::NonZeroUsize;
 use std::path::Path;
+use std::process;
+use std::time::Instant;
+
+use day05::concurrent::simulate;
+use day05::crane::{crane_from_name, crate_mover, Crane, CrateMover9000, CrateMover9001};
+use day05::debugger::{run_session, Debugger};
+use day05::drawing::{parse_drawing, render_drawing};
+use day05::execute::{execute_traced, Policy};
+use day05::formats::{parse_input, write_input, Format};
+use day05::planner::{format_plan, plan_moves};
+use day05::provenance::Provenance;
+use day05::reverse::recover_start;
+use day05::stacks::Stacks;
+use day05::{parse_stacks_instructions, Instruction};
+
+use bench::{apply_baseline, from_baseline, generate_input, to_baseline};
 
-/// A move instruction.
-#[derive(Debug)]
-struct Instruction {
-    n_crates: u32,
-    move_from: u32,
-    move_to: u32,
+/// The labels of the crates on top of each stack, with a `_` for each empty stack.
+fn top_labels(stacks: &Stacks) -> String {
+    stacks.ids().map(|id| stacks.top(id).map_or("_", |some_crate| some_crate.label())).collect()
 }
 
-impl Instruction {
-    fn apply(&self, stacks: &mut HashMap<u32, LinkedList<String>>) {
-        for _ in 0..self.n_crates {
-            let source_stack = stacks.get_mut(&self.move_from).unwrap();
-            let some_crate = source_stack.pop_back().unwrap();
+/// Print the top crates after applying the instructions with each crane, followed by any
+/// stacks left empty. With `--trace`, the stacks are drawn after each instruction, and with
+/// `--lenient`, moves are clamped to the crates available rather than stopping with an error.
+///
+/// Returns whether every crane was able to apply the instructions.
+fn print_top_crates(
+    stacks: &Stacks,
+    listing: &[(usize, Instruction)],
+    trace: bool,
+    policy: Policy,
+) -> bool {
+    let cranes = [("singly-moved", crate_mover(false)), ("multi-moved", crate_mover(true))];
 
-            let dest_stack = stacks.get_mut(&self.move_to).unwrap();
-            dest_stack.push_back(some_crate);
+    let mut succeeded = true;
+    for (name, crane) in cranes {
+        let on_step = |instruction: &Instruction, stacks: &Stacks| {
+            if trace {
+                println!("After {} ({}):\n{}", instruction, name, render_drawing(stacks));
+            }
+        };
+        let execution = match execute_traced(stacks.clone(), listing, crane, policy, on_step) {
+            Ok(execution) => execution,
+            Err(error) => {
+                println!("Unable to apply the {} instructions: {}", name, error);
+                succeeded = false;
+                continue;
+            }
+        };
+
+        println!("Containers in {} stacks: {}", name, top_labels(&execution.stacks));
+        let empty: Vec<String> = execution.empty_stacks().iter().map(ToString::to_string).collect();
+        if !empty.is_empty() {
+            println!("Empty {} stacks: {}", name, empty.join(", "));
+        }
+        if !execution.clamped.is_empty() {
+            println!("Clamped {} {} instructions", execution.clamped.len(), name);
         }
     }
+    succeeded
+}
 
-    fn apply_move_multi(&self, stacks: &mut HashMap<u32, LinkedList<String>>) {
-        let mut to_move = Vec::with_capacity(self.n_crates.try_into().unwrap());
+/// Print the top crates after applying the instructions with each named crane model (see
+/// `crane_from_name`), or every built-in model if none are named.
+fn print_crane_tops(stacks: &Stacks, instructions: &[Instruction], names: &[String]) {
+    let default_names = ["9000", "9001", "limited:2", "limited:3", "alternating", "recording:9001"];
+    let names: Vec<&str> = if names.is_empty() {
+        default_names.to_vec()
+    } else {
+        names.iter().map(String::as_str).collect()
+    };
 
-        let source_stack = stacks.get_mut(&self.move_from).unwrap();
-        for _ in 0..self.n_crates {
-            let some_crate = source_stack.pop_back().unwrap();
-            to_move.push(some_crate);
+    for name in names {
+        let mut crane = crane_from_name(name).expect("Unable to recognise crane model");
+        let mut stacks = stacks.clone();
+        for instruction in instructions {
+            crane.apply(instruction, &mut stacks).expect("Unable to apply instruction");
         }
+        println!("Containers moved by {}: {}", crane.name(), top_labels(&stacks));
+    }
+}
 
-        let dest_stack = stacks.get_mut(&self.move_to).unwrap();
-        for some_crate in to_move.into_iter().rev() {
-            dest_stack.push_back(some_crate);
-        }
+/// Read a drawing of the final stacks from stdin, and print the starting stacks which the
+/// instructions would turn into them.
+fn print_recovered_start<C: Crane>(instructions: &[Instruction], crane: C) {
+    let mut drawing = String::new();
+    stdin().read_to_string(&mut drawing).expect("Unable to read final stacks from stdin");
+    let lines: Vec<&str> = drawing.lines().filter(|line| !line.trim().is_empty()).collect();
+    let final_stacks = parse_drawing(&lines).expect("Unable to parse final stacks");
+
+    match recover_start(&final_stacks, instructions, crane) {
+        Ok(start) => print!("{}", render_drawing(&start)),
+        Err(error) => println!("Unable to recover the starting stacks: {}", error),
     }
 }
 
-fn parse_stacks_instructions(
-    input_string: String,
-) -> (HashMap<u32, LinkedList<String>>, Vec<Instruction>) {
-    let mut lines = input_string.split('\n');
-    let mut stack_lines: Vec<String> = Vec::new();
+/// Read a start drawing, a blank line and a target drawing from stdin, and print a shortest
+/// plan of instructions turning one into the other, as puzzle input.
+fn print_plan<C: Crane>(crane: C, state_limit: usize) {
+    let mut drawings = String::new();
+    stdin().read_to_string(&mut drawings).expect("Unable to read drawings from stdin");
+    let (start, target) = drawings.split_once("\n\n").expect("Unable to find blank line");
+    let start_lines: Vec<&str> = start.lines().collect();
+    let target_lines: Vec<&str> = target.lines().filter(|line| !line.is_empty()).collect();
+    let start = parse_drawing(&start_lines).expect("Unable to parse start stacks");
+    let target = parse_drawing(&target_lines).expect("Unable to parse target stacks");
 
-    loop {
-        let line = lines.next().unwrap();
-        if line.is_empty() {
-            break;
-        }
-        stack_lines.push(line.to_string());
+    match plan_moves(&start, &target, crane, state_limit) {
+        Ok(plan) => print!("{}", format_plan(&start, &plan)),
+        Err(error) => println!("Unable to plan the instructions: {}", error),
     }
+}
 
-    let stack_numbers: Vec<u32> = stack_lines
-        .pop()
-        .unwrap()
-        .chars()
-        .filter(|character| character.is_numeric())
-        .map(|character| character.to_digit(10).unwrap())
-        .collect();
-    let mut stacks: Vec<LinkedList<String>> = vec![LinkedList::new(); stack_numbers.len()];
-
-    for line in stack_lines {
-        let mut start_index: usize = 0;
-        let mut end_index: usize = 3;
-
-        for stack in &mut stacks {
-            if start_index > line.len() || end_index > line.len() {
-                break;
-            };
-            let some_crate = line[start_index..end_index].to_string();
-            if some_crate != "   " {
-                stack.push_front(some_crate);
-            };
-            start_index += 4;
-            end_index += 4;
-        }
+/// Apply the instructions to a copy of the stacks with the crane, printing how long it took.
+fn time_crane(crane: &mut dyn Crane, stacks: &Stacks, instructions: &[Instruction]) -> Stacks {
+    let mut stacks = stacks.clone();
+    let start = Instant::now();
+    for instruction in instructions {
+        crane.apply(instruction, &mut stacks).expect("Unable to apply instruction");
     }
-    let stack_map: HashMap<u32, LinkedList<String>> =
-        stack_numbers.into_iter().zip(stacks.into_iter()).collect();
+    println!("{}: {:.3?}", crane.name(), start.elapsed());
+    stacks
+}
+
+/// Apply the instructions to a copy of the stacks held as the original solution held them,
+/// printing how long it took. Copying the stacks in and out is not timed.
+fn time_baseline(multi: bool, stacks: &Stacks, instructions: &[Instruction]) -> Stacks {
+    let mut baseline = to_baseline(stacks);
+    let start = Instant::now();
+    for instruction in instructions {
+        apply_baseline(instruction, &mut baseline, multi);
+    }
+    println!("{} (linked lists): {:.3?}", crate_mover(multi).name(), start.elapsed());
+    from_baseline(&baseline)
+}
+
+/// Generate a large input and compare the bulk moves with the original solution, which moved
+/// one crate at a time between linked lists.
+fn run_benchmark(n_crates: usize, n_instructions: usize) {
+    let input = generate_input(9, n_crates, n_instructions, 2022);
+    let (stacks, instructions) =
+        parse_stacks_instructions(&input).expect("Unable to parse generated input");
+    println!("{} stacks of {} crates, {} instructions", 9, n_crates, instructions.len());
 
-    let mut instructions: Vec<Instruction> = Vec::new();
-    for line in lines {
-        if line.is_empty() {
-            continue;
+    for multi in [false, true] {
+        let mut bulk = crate_mover(multi);
+        let baseline_stacks = time_baseline(multi, &stacks, &instructions);
+        let bulk_stacks = time_crane(bulk.as_mut(), &stacks, &instructions);
+        assert_eq!(baseline_stacks, bulk_stacks, "Bulk moves changed the output");
+        println!("{} top crates: {}", bulk.name(), top_labels(&bulk_stacks));
+    }
+}
+
+/// Run the instructions with several cranes at once, and print the makespan and conflicts.
+fn print_simulation<C: Crane + Clone>(
+    stacks: &Stacks,
+    instructions: &[Instruction],
+    n_cranes: NonZeroUsize,
+    crane: C,
+) {
+    let simulation = match simulate(stacks, instructions, n_cranes, crane) {
+        Ok(simulation) => simulation,
+        Err(failure) => {
+            println!(
+                "Unable to apply instruction {} ({}) in the serial run: {}",
+                failure.index + 1,
+                failure.instruction,
+                failure.error
+            );
+            return;
         }
-        let line_split: Vec<&str> = line.split(' ').collect();
-        let n_crates = line_split[1].parse::<u32>().unwrap();
-        let move_from = line_split[3].parse::<u32>().unwrap();
-        let move_to = line_split[5].parse::<u32>().unwrap();
+    };
 
-        instructions.push(Instruction { n_crates, move_from, move_to });
+    for conflict in &simulation.conflicts {
+        let cranes: Vec<String> =
+            conflict.cranes.iter().map(|crane| (crane + 1).to_string()).collect();
+        println!(
+            "Tick {}: cranes {} conflict over stack {}",
+            conflict.tick,
+            cranes.join(", "),
+            conflict.stack
+        );
     }
+    for invalid_move in &simulation.invalid_moves {
+        let failure = &invalid_move.failure;
+        println!(
+            "Tick {}: crane {} skipped instruction {} ({}): {}",
+            invalid_move.tick,
+            invalid_move.crane + 1,
+            failure.index + 1,
+            failure.instruction,
+            failure.error
+        );
+    }
+    println!("Makespan with {} cranes: {} ticks", n_cranes, simulation.makespan());
+    println!("Conflicts: {}", simulation.conflicts.len());
+    println!("Skipped instructions: {}", simulation.invalid_moves.len());
+    println!("Containers on top: {}", top_labels(&simulation.stacks));
+    let matches = if simulation.matches_serial { "yes" } else { "no" };
+    println!("Matches serial run: {}", matches);
+}
 
-    (stack_map, instructions)
+/// Read stacks and instructions in any format from stdin, and print them in another.
+fn convert(format: Format) {
+    let mut input = String::new();
+    stdin().read_to_string(&mut input).expect("Unable to read input from stdin");
+    let (stacks, listing) = parse_input(&input, Format::detect(&input))
+        .expect("Unable to parse stacks and instructions");
+    let instructions: Vec<Instruction> =
+        listing.iter().map(|(_, instruction)| *instruction).collect();
+    print!("{}", write_input(&stacks, &instructions, format));
 }
 
+/// Run the advent of code solution. The input may be in any of the formats in
+/// `day05::formats`. With `--trace`, the stacks are drawn after each instruction, and with
+/// `--lenient`, moves are clamped to the crates available.
+///
+/// Supported subcommands are:
+///  - `debug`: step through the instructions with commands read from stdin.
+///  - `cranes [model...]`: print the top crates for each named crane model.
+///  - `reverse`: read the final stacks from stdin and print the starting stacks.
+///  - `plan [--limit n]`: read start and target stacks from stdin and print a shortest plan
+///    between them, exploring at most n (default 100000) arrangements.
+///  - `bench [crates] [instructions]`: time the cranes on a generated input with nine stacks of
+///    the given number of crates (default 100000) and instructions (default 1000).
+///  - `provenance [--json]`: print each crate's history as a table or as JSON.
+///  - `concurrent <n>`: run the instructions with n cranes at once, printing any conflicts
+///    and the makespan.
+///  - `convert <format>`: read stacks and instructions from stdin and print them as a
+///    `drawing`, `compact` or `json`.
+///
+/// `debug`, `reverse`, `plan`, `provenance` and `concurrent` use the CrateMover 9001 if
+/// `--multi` is given.
 fn main() {
     let path = Path::new("./input_01.txt");
     let input_string = read_to_string(path).expect("Unable to read input file 'input_01.txt'");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let has_flag = |flag: &str| args.iter().any(|arg| arg == flag);
 
-    let (mut stack_map, instructions) = parse_stacks_instructions(input_string);
-    let mut multistack_map = stack_map.clone();
+    let (stacks, listing) = parse_input(&input_string, Format::detect(&input_string))
+        .expect("Unable to parse stacks and instructions");
+    let instructions: Vec<Instruction> =
+        listing.iter().map(|(_, instruction)| *instruction).collect();
+    let policy = if has_flag("--lenient") { Policy::Lenient } else { Policy::Strict };
 
-    let mut ordered_keys: Vec<u32> = stack_map.keys().cloned().collect();
-    ordered_keys.sort();
+    let multi = has_flag("--multi");
 
-    for instruction in &instructions {
-        instruction.apply(&mut stack_map);
-        instruction.apply_move_multi(&mut multistack_map);
-    }
-
-    let mut containers: Vec<char> = Vec::new();
-    for key in &ordered_keys {
-        let container = stack_map.get_mut(key).unwrap().pop_back().unwrap();
-        containers.push(container.chars().nth(1).unwrap());
-    }
-    let mut container_string: String = containers.iter().cloned().collect();
-    println!("Containers in singly-moved stacks: {}", container_string);
-
-    containers.clear();
-    for key in &ordered_keys {
-        let container = multistack_map.get_mut(key).unwrap().pop_back().unwrap();
-        containers.push(container.chars().nth(1).unwrap());
+    // Flags such as `--trace` may be given without a subcommand.
+    match args.first().map(String::as_str).filter(|arg| !arg.starts_with("--")) {
+        None => {
+            if !print_top_crates(&stacks, &listing, has_flag("--trace"), policy) {
+                process::exit(1);
+            }
+        }
+        Some("debug") => {
+            let mut debugger = Debugger::new(stacks, &instructions, crate_mover(multi));
+            run_session(&mut debugger, stdin().lock(), stdout().lock())
+                .expect("Unable to run debugging session");
+        }
+        Some("reverse") => print_recovered_start(&instructions, crate_mover(multi)),
+        Some("plan") => {
+            let limit = args.iter().skip_while(|arg| *arg != "--limit").nth(1);
+            let limit = limit.map_or(Ok(100_000), |limit| limit.parse::<usize>());
+            print_plan(crate_mover(multi), limit.expect("Unable to parse state limit"));
+        }
+        Some("bench") => {
+            let number = |index: usize, default: usize| {
+                args.get(index).map_or(Ok(default), |arg| arg.parse::<usize>())
+            };
+            let n_crates = number(1, 100_000).expect("Unable to parse number of crates");
+            let n_instructions = number(2, 1_000).expect("Unable to parse number of instructions");
+            run_benchmark(n_crates, n_instructions);
+        }
+        Some("provenance") => {
+            let provenance = if multi {
+                Provenance::trace(&stacks, &instructions, CrateMover9001)
+            } else {
+                Provenance::trace(&stacks, &instructions, CrateMover9000)
+            };
+            let provenance = provenance.expect("Unable to apply instructions");
+            if has_flag("--json") {
+                println!("{}", provenance.to_json());
+            } else {
+                print!("{}", provenance.to_table());
+            }
+        }
+        Some("convert") => {
+            let format = args.get(1).and_then(|name| Format::from_name(name));
+            convert(format.expect("Unable to recognise format (drawing, compact or json)"));
+        }
+        Some("concurrent") => {
+            let n_cranes = args.get(1).and_then(|arg| arg.parse::<NonZeroUsize>().ok());
+            let n_cranes = n_cranes.expect("Unable to parse number of cranes");
+            if multi {
+                print_simulation(&stacks, &instructions, n_cranes, CrateMover9001);
+            } else {
+                print_simulation(&stacks, &instructions, n_cranes, CrateMover9000);
+            }
+        }
+        Some("cranes") => print_crane_tops(&stacks, &instructions, &args[1..]),
+        Some(_) => {
+            eprintln!(
+                "Usage: day05 [debug|cranes|reverse|plan|bench|provenance|concurrent|convert]"
+            );
+            process::exit(2);
+        }
     }
-    container_string = containers.iter().cloned().collect();
-    println!("Containers in multi-moved stacks: {}", &container_string);
 }
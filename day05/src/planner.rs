@@ -0,0 +1,208 @@
+//! Planning the shortest sequence of instructions which turns one arrangement of the stacks
+//! into another.
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+use crate::crane::Crane;
+use crate::formats::{write_input, Format};
+use crate::stacks::{Crate, Stacks};
+use crate::Instruction;
+
+/// A failure to plan the instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanError {
+    /// The arrangements do not have the same stacks and crates.
+    DifferentCrates,
+    /// More than the limit of arrangements would have to be explored.
+    StateLimit(usize),
+    /// No sequence of instructions reaches the target with the crane.
+    Unreachable,
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::DifferentCrates => {
+                write!(formatter, "the arrangements do not have the same stacks and crates")
+            }
+            PlanError::StateLimit(limit) => {
+                write!(formatter, "no plan found within {} arrangements", limit)
+            }
+            PlanError::Unreachable => write!(formatter, "the target cannot be reached"),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+/// Count the crates with each label in the stacks.
+fn crate_counts(stacks: &Stacks) -> HashMap<&Crate, usize> {
+    let mut counts: HashMap<&Crate, usize> = HashMap::new();
+    for id in stacks.ids() {
+        for some_crate in stacks.stack(id).unwrap_or_default() {
+            *counts.entry(some_crate).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// A lower bound on the number of instructions needed to reach the target.
+///
+/// Each instruction takes crates from one stack and puts them on one other. A stack needs
+/// crates taken from it if any sit above the longest run at its bottom which matches the
+/// target, and needs crates put on it if that run is shorter than the target stack, so at least
+/// as many instructions are needed as there are stacks of either kind.
+fn lower_bound(stacks: &Stacks, target: &Stacks) -> usize {
+    let (mut n_sources, mut n_destinations) = (0, 0);
+    for id in stacks.ids() {
+        let current = stacks.stack(id).unwrap_or_default();
+        let wanted = target.stack(id).unwrap_or_default();
+        let matching = current.iter().zip(wanted).take_while(|(left, right)| left == right).count();
+        if current.len() > matching {
+            n_sources += 1;
+        }
+        if wanted.len() > matching {
+            n_destinations += 1;
+        }
+    }
+    n_sources.max(n_destinations)
+}
+
+/// Every instruction which could be applied to the stacks.
+fn candidate_instructions(stacks: &Stacks) -> Vec<Instruction> {
+    let mut instructions: Vec<Instruction> = Vec::new();
+    for move_from in stacks.ids() {
+        let height = stacks.height(move_from).unwrap_or_default();
+        for move_to in stacks.ids().filter(|id| *id != move_from) {
+            for n_crates in 1..=height {
+                instructions.push(Instruction { n_crates, move_from, move_to });
+            }
+        }
+    }
+    instructions
+}
+
+/// Find a shortest sequence of instructions which turns the start arrangement into the target
+/// when applied with the crane, using A* search. The crane is used from every arrangement
+/// explored, so its lifts should not depend on the instructions it has already carried out.
+///
+/// At most `state_limit` arrangements are explored before giving up.
+pub fn plan_moves<C: Crane>(
+    start: &Stacks,
+    target: &Stacks,
+    mut crane: C,
+    state_limit: usize,
+) -> Result<Vec<Instruction>, PlanError> {
+    if !start.ids().eq(target.ids()) || crate_counts(start) != crate_counts(target) {
+        return Err(PlanError::DifferentCrates);
+    }
+
+    // Each arrangement found, with the arrangement and instruction it was reached from.
+    let mut arrangements: Vec<(Stacks, Option<(usize, Instruction)>)> = vec![(start.clone(), None)];
+    let mut costs: HashMap<Stacks, usize> = HashMap::from([(start.clone(), 0)]);
+    let mut queue = BinaryHeap::from([Reverse((lower_bound(start, target), 0, 0))]);
+
+    while let Some(Reverse((_, cost, index))) = queue.pop() {
+        let stacks = arrangements[index].0.clone();
+        if costs[&stacks] < cost {
+            continue;
+        }
+        if &stacks == target {
+            let mut plan: Vec<Instruction> = Vec::new();
+            let mut current = index;
+            while let Some((previous, instruction)) = arrangements[current].1 {
+                plan.push(instruction);
+                current = previous;
+            }
+            plan.reverse();
+            return Ok(plan);
+        }
+
+        for instruction in candidate_instructions(&stacks) {
+            let mut next = stacks.clone();
+            if crane.apply(&instruction, &mut next).is_err() {
+                continue;
+            }
+            if costs.get(&next).is_some_and(|next_cost| *next_cost <= cost + 1) {
+                continue;
+            }
+            if arrangements.len() >= state_limit {
+                return Err(PlanError::StateLimit(state_limit));
+            }
+
+            costs.insert(next.clone(), cost + 1);
+            queue.push(Reverse((
+                cost + 1 + lower_bound(&next, target),
+                cost + 1,
+                arrangements.len(),
+            )));
+            arrangements.push((next, Some((index, instruction))));
+        }
+    }
+    Err(PlanError::Unreachable)
+}
+
+/// Format the start arrangement and the plan as puzzle input, which can be read back with
+/// `parse_stacks_instructions`.
+pub fn format_plan(start: &Stacks, plan: &[Instruction]) -> String {
+    write_input(start, plan, Format::Drawing)
+}
+
+/// Tests for the move planner.
+#[cfg(test)]
+mod test {
+    use std::collections::{HashSet, VecDeque};
+
+    use super::*;
+    use crate::crane::{crate_mover, CrateMover9000};
+    use crate::drawing::parse_drawing;
+    use crate::{parse_stacks_instructions, EXAMPLE};
+
+    /// The length of a shortest plan, found by breadth-first search.
+    fn shortest_length(start: &Stacks, target: &Stacks, crane: &mut dyn Crane) -> usize {
+        let mut seen: HashSet<Stacks> = HashSet::from([start.clone()]);
+        let mut queue = VecDeque::from([(start.clone(), 0)]);
+        while let Some((stacks, length)) = queue.pop_front() {
+            if &stacks == target {
+                return length;
+            }
+            for instruction in candidate_instructions(&stacks) {
+                let mut next = stacks.clone();
+                if crane.apply(&instruction, &mut next).is_ok() && seen.insert(next.clone()) {
+                    queue.push_back((next, length + 1));
+                }
+            }
+        }
+        panic!("Target is unreachable");
+    }
+
+    /// Test that the plans are as short as possible, and reach the target when read back.
+    #[test]
+    fn test_plan_moves() {
+        let (start, _) = parse_stacks_instructions(EXAMPLE).unwrap();
+        let target = parse_drawing(&["[P]", "[D]", "[C]     [M]", "[Z] [N] [B]", " 1   2   3"]);
+        assert_eq!(
+            plan_moves(&start, &target.unwrap(), CrateMover9000, 1000),
+            Err(PlanError::DifferentCrates)
+        );
+
+        let target =
+            parse_drawing(&["[P]", "[D]", "[C]     [M]", "[Z] [N]", " 1   2   3"]).unwrap();
+        for multi in [false, true] {
+            let mut crane = crate_mover(multi);
+            let plan = plan_moves(&start, &target, crate_mover(multi), 100_000).unwrap();
+            assert_eq!(plan.len(), shortest_length(&start, &target, &mut crane));
+
+            let (mut stacks, instructions) =
+                parse_stacks_instructions(&format_plan(&start, &plan)).unwrap();
+            assert_eq!((&stacks, &instructions), (&start, &plan));
+            for instruction in &instructions {
+                crane.apply(instruction, &mut stacks).unwrap();
+            }
+            assert_eq!(stacks, target);
+        }
+
+        assert_eq!(plan_moves(&start, &target, CrateMover9000, 5), Err(PlanError::StateLimit(5)));
+    }
+}
@@ -0,0 +1,221 @@
+//! Tracking of each crate's identity and history as the instructions are applied.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::crane::{Crane, Lift, RecordingCrane};
+use crate::json::{array, quote};
+use crate::stacks::{Crate, StackError, StackId, Stacks};
+use crate::Instruction;
+
+/// The history of a single crate. Crates are numbered from zero, stack by stack in order of the
+/// stacks' labels, and from bottom to top within each stack.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrateHistory {
+    pub id: usize,
+    pub label: Crate,
+    /// The (zero-based) indices of the instructions which moved the crate.
+    pub moves: Vec<usize>,
+    /// The number of lifts the crane made with the crate.
+    pub lifts: usize,
+    /// The total distance travelled, counting the difference between the stacks' labels.
+    pub distance: u64,
+    /// The stacks the crate was in, from the start to the finish.
+    pub path: Vec<StackId>,
+}
+
+/// Repeat a lift of an instruction on the stacks of crate ids, returning the ids lifted.
+fn lift_ids(
+    id_stacks: &mut BTreeMap<StackId, Vec<usize>>,
+    instruction: &Instruction,
+    lift: &Lift,
+) -> Vec<usize> {
+    let source = id_stacks.entry(instruction.move_from).or_default();
+    let mut lifted = source.split_off(source.len().saturating_sub(lift.n_crates));
+    let moved = lifted.clone();
+    if lift.reversed {
+        lifted.reverse();
+    }
+    id_stacks.entry(instruction.move_to).or_default().append(&mut lifted);
+    moved
+}
+
+/// The histories of every crate after applying some instructions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Provenance {
+    pub crates: Vec<CrateHistory>,
+    /// The stacks which no crate was taken from or put on.
+    pub untouched: Vec<StackId>,
+}
+
+impl Provenance {
+    /// Apply the instructions with the crane, tracking each crate. The crane's lifts are repeated
+    /// on stacks of crate ids alongside the stacks themselves, so that crates with the same
+    /// label can be told apart.
+    pub fn trace<C: Crane>(
+        stacks: &Stacks,
+        instructions: &[Instruction],
+        crane: C,
+    ) -> Result<Provenance, StackError> {
+        let mut id_stacks: BTreeMap<StackId, Vec<usize>> = BTreeMap::new();
+        let mut crates: Vec<CrateHistory> = Vec::new();
+        for id in stacks.ids() {
+            let id_stack = id_stacks.entry(id).or_default();
+            for some_crate in stacks.stack(id)? {
+                id_stack.push(crates.len());
+                crates.push(CrateHistory {
+                    id: crates.len(),
+                    label: some_crate.clone(),
+                    moves: Vec::new(),
+                    lifts: 0,
+                    distance: 0,
+                    path: vec![id],
+                });
+            }
+        }
+
+        let mut touched: BTreeSet<StackId> = BTreeSet::new();
+        let mut moved_stacks = stacks.clone();
+        let mut crane = RecordingCrane::new(crane);
+        for (index, instruction) in instructions.iter().enumerate() {
+            let n_lifts = crane.lifts.len();
+            crane.apply(instruction, &mut moved_stacks)?;
+
+            let mut moved: BTreeSet<usize> = BTreeSet::new();
+            for recorded in &crane.lifts[n_lifts..] {
+                touched.extend([instruction.move_from, instruction.move_to]);
+                let lifted = lift_ids(&mut id_stacks, instruction, &recorded.lift);
+                for id in lifted {
+                    crates[id].lifts += 1;
+                    moved.insert(id);
+                }
+            }
+            for id in moved {
+                let history = &mut crates[id];
+                history.moves.push(index);
+                history.distance += u64::from(instruction.move_from.abs_diff(instruction.move_to));
+                history.path.push(instruction.move_to);
+            }
+        }
+
+        let untouched = stacks.ids().filter(|id| !touched.contains(id)).collect();
+        Ok(Provenance { crates, untouched })
+    }
+
+    /// The crate moved by the most instructions, breaking ties by the distance travelled and
+    /// then by the lowest id.
+    pub fn most_moved(&self) -> Option<&CrateHistory> {
+        self.crates.iter().min_by_key(|history| {
+            (std::cmp::Reverse((history.moves.len(), history.distance)), history.id)
+        })
+    }
+
+    /// Format the report as a table, with a row for each crate.
+    pub fn to_table(&self) -> String {
+        let mut table = match self.most_moved() {
+            Some(history) => format!(
+                "Most moved crate: {} (id {}), moved {} times\n",
+                history.label,
+                history.id,
+                history.moves.len()
+            ),
+            None => "Most moved crate: none\n".to_string(),
+        };
+        let untouched: Vec<String> = self.untouched.iter().map(StackId::to_string).collect();
+        let untouched =
+            if untouched.is_empty() { "none".to_string() } else { untouched.join(", ") };
+        table.push_str(&format!("Untouched stacks: {}\n", untouched));
+
+        let label_width = self.crates.iter().map(|history| history.label.label().len());
+        let label_width = label_width.max().unwrap_or(0).max(5);
+        table.push_str(&format!(
+            "{:>5} {:<label_width$} {:>5} {:>5} {:>8}  path\n",
+            "id",
+            "crate",
+            "moves",
+            "lifts",
+            "distance",
+            label_width = label_width
+        ));
+        for history in &self.crates {
+            let path: Vec<String> = history.path.iter().map(StackId::to_string).collect();
+            table.push_str(&format!(
+                "{:>5} {:<label_width$} {:>5} {:>5} {:>8}  {}\n",
+                history.id,
+                history.label.label(),
+                history.moves.len(),
+                history.lifts,
+                history.distance,
+                path.join(" -> "),
+                label_width = label_width
+            ));
+        }
+        table
+    }
+
+    /// Format the report as JSON.
+    pub fn to_json(&self) -> String {
+        let most_moved =
+            self.most_moved().map_or("null".to_string(), |history| history.id.to_string());
+        let untouched = array(self.untouched.iter().map(StackId::to_string));
+        let crates = array(self.crates.iter().map(|history| {
+            format!(
+                "{{\"id\":{},\"label\":{},\"moves\":{},\"lifts\":{},\"distance\":{},\"path\":{}}}",
+                history.id,
+                quote(history.label.label()),
+                array(history.moves.iter().map(usize::to_string)),
+                history.lifts,
+                history.distance,
+                array(history.path.iter().map(StackId::to_string))
+            )
+        }));
+        format!(
+            "{{\"most_moved\":{},\"untouched\":{},\"crates\":{}}}",
+            most_moved, untouched, crates
+        )
+    }
+}
+
+/// Tests for tracking the crates.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::{parse_stacks_instructions, EXAMPLE};
+
+    /// Test the histories, most moved crate and untouched stacks for the example.
+    #[test]
+    fn test_trace() {
+        let example = EXAMPLE.replace(" 1   2   3\n", " 1   2   3   4\n");
+        let (stacks, instructions) = parse_stacks_instructions(&example).unwrap();
+        let provenance = Provenance::trace(&stacks, &instructions, CrateMover9000).unwrap();
+
+        let most_moved = provenance.most_moved().unwrap();
+        assert_eq!((most_moved.id, most_moved.label.label()), (4, "D"));
+        assert_eq!(most_moved.moves, vec![0, 1]);
+        assert_eq!((most_moved.lifts, most_moved.distance), (2, 3));
+        assert_eq!(most_moved.path, vec![2, 1, 3]);
+        assert_eq!(provenance.crates[5].path, vec![3]);
+        assert_eq!(provenance.untouched, vec![4]);
+
+        let json = provenance.to_json();
+        assert!(json.starts_with("{\"most_moved\":4,\"untouched\":[4],\"crates\":[{\"id\":0,"));
+        assert!(json.contains(
+            "{\"id\":4,\"label\":\"D\",\"moves\":[0,1],\"lifts\":2,\"distance\":3,\"path\":[2,1,3]}"
+        ));
+
+        let multi = Provenance::trace(&stacks, &instructions, CrateMover9001).unwrap();
+        assert_eq!(multi.crates[0].path, provenance.crates[0].path);
+        assert_eq!(multi.crates[2].path, vec![2, 1]);
+        assert_eq!(provenance.crates[2].path, vec![2, 1, 2]);
+    }
+
+    /// Test that crates with the same label are told apart.
+    #[test]
+    fn test_trace_duplicate_labels() {
+        let (stacks, instructions) =
+            parse_stacks_instructions("[A]\n[A] [A]\n 1   2\n\nmove 1 from 1 to 2\n").unwrap();
+        let provenance = Provenance::trace(&stacks, &instructions, CrateMover9000).unwrap();
+        let paths: Vec<Vec<StackId>> =
+            provenance.crates.iter().map(|history| history.path.clone()).collect();
+        assert_eq!(paths, vec![vec![1], vec![1, 2], vec![2]]);
+    }
+}
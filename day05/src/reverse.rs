@@ -0,0 +1,93 @@
+//! Recovery of the starting stacks from the stacks left after the instructions.
+use std::fmt;
+
+use crate::crane::Crane;
+use crate::debugger::{Debugger, StepError};
+use crate::stacks::Stacks;
+use crate::Instruction;
+
+/// A failure to recover the starting stacks.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReverseError {
+    /// An instruction could not be undone from the final stacks.
+    Undo(StepError),
+    /// An instruction could not be replayed from the recovered starting stacks.
+    Replay(StepError),
+    /// Replaying the instructions from the recovered starting stacks gave different final stacks.
+    Mismatch,
+}
+
+impl fmt::Display for ReverseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (verb, failure) = match self {
+            ReverseError::Undo(failure) => ("undo", failure),
+            ReverseError::Replay(failure) => ("replay", failure),
+            ReverseError::Mismatch => {
+                return write!(formatter, "replaying the instructions gave different stacks")
+            }
+        };
+        write!(
+            formatter,
+            "unable to {} instruction {} ({}): {}",
+            verb,
+            failure.index + 1,
+            failure.instruction,
+            failure.error
+        )
+    }
+}
+
+impl std::error::Error for ReverseError {}
+
+/// Recover the starting stacks from the final stacks, by applying the inverse of each
+/// instruction in reverse order with the same crane.
+///
+/// The instructions are then replayed from the recovered stacks, to check that they give the
+/// final stacks back.
+pub fn recover_start<C: Crane>(
+    final_stacks: &Stacks,
+    instructions: &[Instruction],
+    crane: C,
+) -> Result<Stacks, ReverseError> {
+    let mut debugger = Debugger::at_end(final_stacks.clone(), instructions, crane);
+    debugger.jump(0).map_err(ReverseError::Undo)?;
+    let start = debugger.stacks().clone();
+
+    debugger.jump(instructions.len()).map_err(ReverseError::Replay)?;
+    if debugger.stacks() != final_stacks {
+        return Err(ReverseError::Mismatch);
+    }
+    Ok(start)
+}
+
+/// Tests for recovering the starting stacks.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::crane::{crate_mover, CrateMover9000};
+    use crate::{parse_stacks_instructions, EXAMPLE};
+
+    /// Test that the starting stacks are recovered with either crane.
+    #[test]
+    fn test_recover_start() {
+        let (start, instructions) = parse_stacks_instructions(EXAMPLE).unwrap();
+
+        for multi in [false, true] {
+            let mut stacks = start.clone();
+            for instruction in &instructions {
+                crate_mover(multi).apply(instruction, &mut stacks).unwrap();
+            }
+            assert_eq!(
+                recover_start(&stacks, &instructions, crate_mover(multi)),
+                Ok(start.clone())
+            );
+        }
+
+        let error = recover_start(&start, &instructions, CrateMover9000).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unable to undo instruction 2 (move 3 from 3 to 1): cannot take 3 crates from stack 3, \
+             which holds 1"
+        );
+    }
+}
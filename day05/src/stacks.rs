@@ -0,0 +1,238 @@
+//! Crates, and the numbered stacks which hold them.
+use std::fmt;
+
+/// The number labelling a stack.
+pub type StackId = u32;
+
+/// A crate, labelled with the letter(s) drawn inside its brackets.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Crate(String);
+
+impl Crate {
+    /// Create a crate with the given label.
+    pub fn new(label: &str) -> Crate {
+        Crate(label.to_string())
+    }
+
+    /// Parse a crate from its drawing, e.g. `[Z]`.
+    pub fn parse(drawing: &str) -> Option<Crate> {
+        let label = drawing.strip_prefix('[')?.strip_suffix(']')?;
+        if label.is_empty() {
+            return None;
+        }
+        Some(Crate::new(label))
+    }
+
+    /// The crate's label.
+    pub fn label(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Crate {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+/// An error from reading or moving the crates in some stacks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackError {
+    /// There is no stack with the given label.
+    UnknownStack(StackId),
+    /// The stack has no crates.
+    EmptyStack(StackId),
+    /// The stack holds fewer crates than were requested.
+    NotEnoughCrates { stack: StackId, requested: usize, available: usize },
+}
+
+impl fmt::Display for StackError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StackError::UnknownStack(stack) => write!(formatter, "there is no stack {}", stack),
+            StackError::EmptyStack(stack) => write!(formatter, "stack {} is empty", stack),
+            StackError::NotEnoughCrates { stack, requested, available } => write!(
+                formatter,
+                "cannot take {} crates from stack {}, which holds {}",
+                requested, stack, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StackError {}
+
+/// Stacks of crates, indexed by their labels. Each stack is ordered from bottom to top.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Stacks {
+    /// The labels of the stacks, in ascending order.
+    ids: Vec<StackId>,
+    /// The crates in each stack, in the same order as the labels.
+    stacks: Vec<Vec<Crate>>,
+}
+
+impl Stacks {
+    /// Create an empty stack for each label.
+    pub fn new<I: IntoIterator<Item = StackId>>(ids: I) -> Stacks {
+        let mut ids: Vec<StackId> = ids.into_iter().collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let stacks = vec![Vec::new(); ids.len()];
+        Stacks { ids, stacks }
+    }
+
+    /// The position of a stack in the stacks.
+    fn index(&self, id: StackId) -> Result<usize, StackError> {
+        self.ids.binary_search(&id).map_err(|_| StackError::UnknownStack(id))
+    }
+
+    /// The labels of the stacks, in ascending order.
+    pub fn ids(&self) -> impl Iterator<Item = StackId> + '_ {
+        self.ids.iter().copied()
+    }
+
+    /// The crates in a stack, from bottom to top.
+    pub fn stack(&self, id: StackId) -> Result<&[Crate], StackError> {
+        Ok(&self.stacks[self.index(id)?])
+    }
+
+    /// The number of crates in a stack.
+    pub fn height(&self, id: StackId) -> Result<usize, StackError> {
+        self.stack(id).map(<[Crate]>::len)
+    }
+
+    /// The crate on top of a stack.
+    pub fn top(&self, id: StackId) -> Result<&Crate, StackError> {
+        self.stack(id)?.last().ok_or(StackError::EmptyStack(id))
+    }
+
+    /// The crate on top of each stack, in order of the stacks' labels.
+    pub fn tops(&self) -> Result<Vec<&Crate>, StackError> {
+        self.ids().map(|id| self.top(id)).collect()
+    }
+
+    /// Put a crate on top of a stack.
+    pub fn push(&mut self, id: StackId, some_crate: Crate) -> Result<(), StackError> {
+        let index = self.index(id)?;
+        self.stacks[index].push(some_crate);
+        Ok(())
+    }
+
+    /// Take the crate from the top of a stack.
+    pub fn pop(&mut self, id: StackId) -> Result<Crate, StackError> {
+        let index = self.index(id)?;
+        self.stacks[index].pop().ok_or(StackError::EmptyStack(id))
+    }
+
+    /// Check that `n_crates` crates can be moved between the stacks.
+    pub fn check_move(
+        &self,
+        n_crates: usize,
+        from: StackId,
+        to: StackId,
+    ) -> Result<(), StackError> {
+        self.index(to)?;
+        let available = self.height(from)?;
+        if n_crates > available {
+            return Err(StackError::NotEnoughCrates {
+                stack: from,
+                requested: n_crates,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    /// Move the top `n_crates` crates from one stack to another in a single lift, putting them
+    /// down in reverse order if `reversed` (as if they were moved one at a time).
+    fn lift_crates(
+        &mut self,
+        n_crates: usize,
+        from: StackId,
+        to: StackId,
+        reversed: bool,
+    ) -> Result<(), StackError> {
+        self.check_move(n_crates, from, to)?;
+        if from == to {
+            return Ok(());
+        }
+        let (from_index, to_index) = (self.index(from)?, self.index(to)?);
+
+        // Take both stacks at once, so the crates can be drained from one into the other.
+        let (source_stack, dest_stack) = if from_index < to_index {
+            let (left, right) = self.stacks.split_at_mut(to_index);
+            (&mut left[from_index], &mut right[0])
+        } else {
+            let (left, right) = self.stacks.split_at_mut(from_index);
+            (&mut right[0], &mut left[to_index])
+        };
+        let moved = source_stack.drain(source_stack.len() - n_crates..);
+        if reversed {
+            dest_stack.extend(moved.rev());
+        } else {
+            dest_stack.extend(moved);
+        }
+        Ok(())
+    }
+
+    /// Move the top `n_crates` crates from one stack to another, keeping their order. The
+    /// stacks are left unchanged if the move is not possible.
+    pub fn move_crates(
+        &mut self,
+        n_crates: usize,
+        from: StackId,
+        to: StackId,
+    ) -> Result<(), StackError> {
+        self.lift_crates(n_crates, from, to, false)
+    }
+
+    /// Move the top `n_crates` crates from one stack to another, reversing their order as if
+    /// they were moved one at a time. The stacks are left unchanged if the move is not
+    /// possible.
+    pub fn move_crates_reversed(
+        &mut self,
+        n_crates: usize,
+        from: StackId,
+        to: StackId,
+    ) -> Result<(), StackError> {
+        self.lift_crates(n_crates, from, to, true)
+    }
+}
+
+/// Tests for the crate stacks.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn labels(stacks: &Stacks, id: StackId) -> Vec<&str> {
+        stacks.stack(id).unwrap().iter().map(Crate::label).collect()
+    }
+
+    /// Test reading and moving crates, and the errors for bad moves.
+    #[test]
+    fn test_stacks() {
+        let mut stacks = Stacks::new([1, 2]);
+        for label in ["A", "B", "C"] {
+            stacks.push(1, Crate::new(label)).unwrap();
+        }
+
+        assert_eq!(stacks.top(2), Err(StackError::EmptyStack(2)));
+        assert_eq!(stacks.tops(), Err(StackError::EmptyStack(2)));
+        stacks.move_crates(2, 1, 2).unwrap();
+        assert_eq!(labels(&stacks, 1), vec!["A"]);
+        assert_eq!(labels(&stacks, 2), vec!["B", "C"]);
+        assert_eq!(stacks.tops().unwrap(), vec![&Crate::new("A"), &Crate::new("C")]);
+
+        assert_eq!(stacks.move_crates(1, 3, 1), Err(StackError::UnknownStack(3)));
+        assert_eq!(
+            stacks.move_crates(3, 2, 1),
+            Err(StackError::NotEnoughCrates { stack: 2, requested: 3, available: 2 })
+        );
+        assert_eq!(stacks.height(2), Ok(2));
+
+        stacks.move_crates_reversed(2, 2, 1).unwrap();
+        assert_eq!(labels(&stacks, 1), vec!["A", "C", "B"]);
+        stacks.move_crates_reversed(2, 1, 1).unwrap();
+        assert_eq!(labels(&stacks, 1), vec!["A", "C", "B"]);
+    }
+}
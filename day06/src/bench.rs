@@ -0,0 +1,83 @@
+//! Generation of large signals, and the original marker scan for benchmarking the linear one.
+use std::collections::{HashSet, LinkedList};
+
+/// A linear congruential generator, so that generated signals are reproducible.
+struct Random(u64);
+
+impl Random {
+    /// A pseudo-random number below the bound.
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as usize
+    }
+}
+
+/// Generate a signal of `length` characters, drawn from the first `alphabet` (at most 26)
+/// lowercase letters. A marker longer than the alphabet can never be found in it.
+pub fn generate_stream(length: usize, alphabet: usize, seed: u64) -> String {
+    let mut random = Random(seed);
+    (0..length).map(|_| char::from(b'a' + random.below(alphabet.clamp(1, 26)) as u8)).collect()
+}
+
+/// Find the markers as the original solution did, by collecting the characters of the longest
+/// window into a list and counting the distinct characters at the end of it with a new set for
+/// every character. Positions are counted in characters, which are the same as the bytes
+/// counted by `find_markers` for ASCII signals.
+pub fn find_markers_naive(stream: &str, windows: &[usize]) -> Vec<Option<usize>> {
+    let mut marker_ends: Vec<Option<usize>> =
+        windows.iter().map(|window| if *window == 0 { Some(0) } else { None }).collect();
+    let longest_window = windows.iter().copied().max().unwrap_or(0);
+
+    let mut characters: LinkedList<char> = LinkedList::new();
+    for (index, character) in stream.chars().enumerate() {
+        if marker_ends.iter().all(Option::is_some) {
+            break;
+        }
+        characters.push_back(character);
+        if characters.len() > longest_window {
+            characters.pop_front();
+        };
+
+        let mut character_set: HashSet<&char> = HashSet::new();
+        let mut last_n_unique = 0;
+        for seen_character in characters.iter().rev() {
+            character_set.insert(seen_character);
+
+            if character_set.len() != (last_n_unique + 1) {
+                break;
+            };
+            last_n_unique += 1;
+        }
+
+        for (marker_end, window) in marker_ends.iter_mut().zip(windows) {
+            if marker_end.is_none() && last_n_unique >= *window {
+                *marker_end = Some(index + 1);
+            }
+        }
+    }
+    marker_ends
+}
+
+/// Tests for the original marker scan.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use day06::find_markers;
+
+    /// Test that the linear scan finds the same markers as the original on random signals.
+    #[test]
+    fn test_linear_scan_matches_naive() {
+        let mut random = Random(6);
+        for seed in 0..500 {
+            let stream = generate_stream(random.below(200), 1 + random.below(20), seed);
+            let windows: Vec<usize> = (0..1 + random.below(4)).map(|_| random.below(22)).collect();
+            assert_eq!(
+                find_markers(stream.as_bytes(), &windows),
+                find_markers_naive(&stream, &windows),
+                "Markers differ for {:?} in {:?}",
+                windows,
+                stream
+            );
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Splitting a whole transmission into segments, each framed by a packet or message marker.
+
+use crate::{MESSAGE_WINDOW, PACKET_WINDOW};
+
+/// Find the byte offsets of every non-overlapping marker of `window` distinct bytes. Each
+/// marker is `start..end`, and the search for the next one begins after the end of the last.
+/// A window of zero finds no markers.
+pub fn find_all_markers(stream: &[u8], window: usize) -> Vec<(usize, usize)> {
+    let mut markers: Vec<(usize, usize)> = Vec::new();
+    if window == 0 {
+        return markers;
+    }
+
+    let mut last_seen = [0; 256];
+    let mut run_start = 0;
+    for (index, byte) in stream.iter().enumerate() {
+        run_start = run_start.max(last_seen[usize::from(*byte)]);
+        last_seen[usize::from(*byte)] = index + 1;
+        if index + 1 - run_start >= window {
+            markers.push((index + 1 - window, index + 1));
+            run_start = index + 1;
+        }
+    }
+    markers
+}
+
+/// The kind of a segment of the transmission.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SegmentKind {
+    /// Bytes before the first marker.
+    Preamble,
+    /// A start-of-packet marker and the bytes after it.
+    Packet,
+    /// A start-of-message marker and the bytes after it.
+    Message,
+}
+
+impl SegmentKind {
+    /// The name of the kind, as shown in the segment table.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SegmentKind::Preamble => "preamble",
+            SegmentKind::Packet => "packet",
+            SegmentKind::Message => "message",
+        }
+    }
+}
+
+/// A segment of the transmission, running from its marker up to the next marker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub kind: SegmentKind,
+    /// The byte offset of the start of the segment, including its marker.
+    pub offset: usize,
+    /// The number of bytes in the segment, including its marker.
+    pub length: usize,
+    /// The number of bytes in the segment's marker.
+    pub marker_length: usize,
+}
+
+impl Segment {
+    /// The byte offset of the payload after the marker.
+    pub fn payload_offset(&self) -> usize {
+        self.offset + self.marker_length
+    }
+
+    /// The number of bytes in the payload after the marker.
+    pub fn payload_length(&self) -> usize {
+        self.length - self.marker_length
+    }
+}
+
+/// Split the transmission into segments at every start-of-packet and start-of-message marker.
+///
+/// Every start-of-message marker contains start-of-packet markers, so message markers are
+/// found first and packet markers are only looked for in the gaps between them: before the
+/// first message marker, between the end of each message marker and the start of the next,
+/// and after the last. Each segment runs up to the next marker of either kind, so a message's
+/// payload ends at the first packet marker after it. Any bytes before the first marker form a
+/// preamble.
+pub fn split_segments(stream: &[u8]) -> Vec<Segment> {
+    let messages = find_all_markers(stream, MESSAGE_WINDOW);
+    let gaps = (0..=messages.len()).map(|index| {
+        let gap_start = if index == 0 { 0 } else { messages[index - 1].1 };
+        let gap_end = messages.get(index).map_or(stream.len(), |(start, _)| *start);
+        (gap_start, gap_end)
+    });
+
+    let mut markers: Vec<(usize, usize, SegmentKind)> =
+        messages.iter().map(|(start, end)| (*start, *end, SegmentKind::Message)).collect();
+    for (gap_start, gap_end) in gaps {
+        for (start, end) in find_all_markers(&stream[gap_start..gap_end], PACKET_WINDOW) {
+            markers.push((gap_start + start, gap_start + end, SegmentKind::Packet));
+        }
+    }
+    markers.sort_unstable_by_key(|(start, _, _)| *start);
+
+    let first_start = markers.first().map_or(stream.len(), |(start, _, _)| *start);
+    let mut segments: Vec<Segment> = Vec::new();
+    if first_start > 0 {
+        segments.push(Segment {
+            kind: SegmentKind::Preamble,
+            offset: 0,
+            length: first_start,
+            marker_length: 0,
+        });
+    }
+    for (index, (start, end, kind)) in markers.iter().enumerate() {
+        let next_start = markers.get(index + 1).map_or(stream.len(), |(next, _, _)| *next);
+        segments.push(Segment {
+            kind: *kind,
+            offset: *start,
+            length: next_start - start,
+            marker_length: end - start,
+        });
+    }
+    segments
+}
+
+/// Format the segments as a table, with a row for each segment.
+pub fn segment_table(segments: &[Segment]) -> String {
+    let mut table = format!(
+        "{:<8} {:>8} {:>8} {:>6} {:>8} {:>8}\n",
+        "kind", "offset", "length", "marker", "payload", "bytes"
+    );
+    for segment in segments {
+        table.push_str(&format!(
+            "{:<8} {:>8} {:>8} {:>6} {:>8} {:>8}\n",
+            segment.kind.name(),
+            segment.offset,
+            segment.length,
+            segment.marker_length,
+            segment.payload_offset(),
+            segment.payload_length()
+        ));
+    }
+    table
+}
+
+/// Tests for splitting the transmission into segments.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Test finding every marker, and the segments they frame, with packet markers found after
+    /// each message marker.
+    #[test]
+    fn test_split_segments() {
+        assert_eq!(find_all_markers(b"abcdabcd", 4), vec![(0, 4), (4, 8)]);
+        assert_eq!(find_all_markers(b"aabcdaxyz", 4), vec![(1, 5), (5, 9)]);
+        assert_eq!(find_all_markers(b"abcabc", 0), vec![]);
+
+        let stream = b"aaaabcdeeeabcdefghijklmnwxyzab";
+        let segments: Vec<(SegmentKind, usize, usize, usize)> = split_segments(stream)
+            .iter()
+            .map(|segment| (segment.kind, segment.offset, segment.length, segment.marker_length))
+            .collect();
+        assert_eq!(
+            segments,
+            vec![
+                (SegmentKind::Preamble, 0, 3, 0),
+                (SegmentKind::Packet, 3, 7, 4),
+                (SegmentKind::Message, 10, 14, 14),
+                (SegmentKind::Packet, 24, 6, 4),
+            ]
+        );
+
+        let segments = split_segments(b"xxabcdxxxabcdefghijklmnopqqqqqqqqwxyzqqqq");
+        let kinds: Vec<(SegmentKind, usize)> =
+            segments.iter().map(|segment| (segment.kind, segment.offset)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (SegmentKind::Preamble, 0),
+                (SegmentKind::Packet, 1),
+                (SegmentKind::Message, 8),
+                (SegmentKind::Packet, 22),
+                (SegmentKind::Packet, 32),
+            ]
+        );
+
+        let segments = split_segments(b"abcdefghijklmnwxyzzabcdefghijklmn");
+        let segments: Vec<(usize, usize, usize)> = segments
+            .iter()
+            .map(|segment| (segment.offset, segment.payload_offset(), segment.payload_length()))
+            .collect();
+        assert_eq!(segments, vec![(0, 14, 0), (14, 18, 0), (18, 32, 1)]);
+        assert_eq!(
+            split_segments(b"aaaa"),
+            vec![Segment { kind: SegmentKind::Preamble, offset: 0, length: 4, marker_length: 0 }]
+        );
+        assert!(split_segments(b"").is_empty());
+    }
+}
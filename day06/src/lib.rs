@@ -0,0 +1,68 @@
+//! Marker detection in the signal for the sixth advent of code problem.
+pub mod frames;
+pub mod stream;
+
+use stream::Decoder;
+
+/// The number of distinct bytes in a start-of-packet marker.
+pub const PACKET_WINDOW: usize = 4;
+/// The number of distinct bytes in a start-of-message marker.
+pub const MESSAGE_WINDOW: usize = 14;
+
+/// Find the number of bytes read when the first marker of each window size completes, i.e.
+/// when the last `window` bytes are all different. Each window size gets its own result, which
+/// is `None` if the stream has no such marker.
+///
+/// The signal is treated as bytes rather than characters: a non-ASCII character counts as each
+/// of the bytes which encode it, and positions are byte offsets. For the puzzle's signals, which
+/// are ASCII, these are the same as the characters.
+///
+/// The decoder keeps the position of the last occurrence of each byte, so this takes linear
+/// time whatever the window sizes.
+pub fn find_markers(stream: &[u8], windows: &[usize]) -> Vec<Option<usize>> {
+    let mut marker_ends: Vec<Option<usize>> = vec![None; windows.len()];
+    for event in Decoder::new(windows).feed(stream) {
+        for (marker_end, window) in marker_ends.iter_mut().zip(windows) {
+            if *window == event.window {
+                *marker_end = Some(event.end);
+            }
+        }
+    }
+    marker_ends
+}
+
+/// Find the number of bytes read when the first marker of `window` distinct bytes completes,
+/// if there is one.
+pub fn find_marker(stream: &[u8], window: usize) -> Option<usize> {
+    find_markers(stream, &[window])[0]
+}
+
+/// Tests for finding markers.
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Test the puzzle's examples, windows which are too long to be found, and that positions in
+    /// non-ASCII signals are byte offsets.
+    #[test]
+    fn test_find_markers() {
+        let examples = [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+        ];
+        for (stream, packet_end, message_end) in examples {
+            assert_eq!(find_marker(stream.as_bytes(), PACKET_WINDOW), Some(packet_end));
+            assert_eq!(
+                find_markers(stream.as_bytes(), &[MESSAGE_WINDOW, PACKET_WINDOW, 27]),
+                vec![Some(message_end), Some(packet_end), None]
+            );
+        }
+
+        assert_eq!(find_marker(b"abcdefghijklmnopqrstuvwxyz", 26), Some(26));
+        assert_eq!(find_markers(b"aab", &[0, 1, 2, 3]), vec![Some(0), Some(1), Some(3), None]);
+        assert_eq!(find_marker("ééab".as_bytes(), 3), Some(5));
+    }
+}
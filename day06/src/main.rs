@@ -1,60 +1,139 @@
 //! Solution to the sixth advent of code problem.
-use std::collections::{HashSet, LinkedList};
-use std::fs::read_to_string;
+mod bench;
+
+use std::env;
+use std::fs::{read_to_string, File};
+use std::io::{stdin, stdout, Read, Write};
 use std::path::Path;
+use std::time::Instant;
 
-type PacketMarkerEnd = usize;
-type MessageMarkerEnd = usize;
-
-/// Identify the packet and message end characters from the message string.
-fn identify_markers(message: String) -> Option<(PacketMarkerEnd, MessageMarkerEnd)> {
-    let mut packet_marker_end: Option<usize> = None;
-    let mut message_marker_end: Option<usize> = None;
-
-    let mut characters: LinkedList<char> = LinkedList::new();
-    for (index, character) in message.chars().enumerate() {
-        characters.push_back(character);
-        if characters.len() > 14 {
-            characters.pop_front();
-        };
-
-        let mut character_set: HashSet<&char> = HashSet::new();
-        let mut last_n_unique = 0;
-        for seen_character in characters.iter().rev() {
-            character_set.insert(seen_character);
-
-            if character_set.len() != (last_n_unique + 1) {
-                break;
-            };
-            last_n_unique += 1;
-        }
+use day06::frames::{segment_table, split_segments, SegmentKind};
+use day06::stream::{decode_stream, Decoder};
+use day06::{find_markers, MESSAGE_WINDOW, PACKET_WINDOW};
+
+use bench::{find_markers_naive, generate_stream};
+
+/// Time both marker scans on a generated signal of `megabytes` megabytes, which has no
+/// start-of-message marker so that the whole signal is scanned.
+fn run_benchmark(megabytes: usize) {
+    let stream = generate_stream(megabytes * 1024 * 1024, MESSAGE_WINDOW - 1, 2022);
+    let windows = [PACKET_WINDOW, MESSAGE_WINDOW];
+    println!("Scanning {} bytes for windows {:?}", stream.len(), windows);
+
+    let start = Instant::now();
+    let naive_ends = find_markers_naive(&stream, &windows);
+    println!("Naive scan: {:.3?}", start.elapsed());
+
+    let start = Instant::now();
+    let linear_ends = find_markers(stream.as_bytes(), &windows);
+    println!("Linear scan: {:.3?}", start.elapsed());
 
-        if packet_marker_end.is_none() && last_n_unique == 4 {
-            packet_marker_end = Some(index + 1);
-        };
-        if message_marker_end.is_none() && last_n_unique == 14 {
-            message_marker_end = Some(index + 1);
-            break;
-        };
+    assert_eq!(naive_ends, linear_ends, "The scans found different markers");
+    println!("Markers: {:?}", linear_ends);
+}
+
+/// Decode the signal from a reader as it arrives, printing each marker as soon as it completes.
+fn print_stream_markers<R: Read>(reader: R, windows: &[usize]) {
+    let mut decoder = Decoder::new(windows);
+    decode_stream(reader, &mut decoder, |event| {
+        println!("The marker of {} distinct bytes ends at byte {}", event.window, event.end);
+        stdout().flush().expect("Unable to write to stdout");
+    })
+    .expect("Unable to read signal");
+
+    if !decoder.is_done() {
+        println!("Signal ended after {} bytes with markers missing.", decoder.position());
     }
+}
+
+/// Split a whole transmission into segments at every packet and message marker, and print them
+/// as a table followed by the number of segments of each kind.
+fn print_segments(stream: &[u8]) {
+    let segments = split_segments(stream);
+    print!("{}", segment_table(&segments));
 
-    if let Some(packet_marker_end) = packet_marker_end {
-        if let Some(message_marker_end) = message_marker_end {
-            return Some((packet_marker_end, message_marker_end));
-        };
-    };
-    None
+    let count = |kind: SegmentKind| segments.iter().filter(|segment| segment.kind == kind).count();
+    println!(
+        "{} bytes in {} packets and {} messages",
+        stream.len(),
+        count(SegmentKind::Packet),
+        count(SegmentKind::Message)
+    );
 }
 
+/// Run the advent of code solution.
+///
+/// The signal is read as bytes, and markers are reported by the number of bytes read when they
+/// complete, which is the number of characters for an ASCII signal. Each `--window <n>`
+/// argument looks for the first marker of n distinct bytes instead of the packet and message
+/// markers. With the `bench` subcommand, compare the marker scans on a
+/// generated signal of the given number of megabytes (default 8). With the `stream`
+/// subcommand, decode the signal from stdin (or the file or named pipe given) as it arrives,
+/// printing each marker as soon as it completes. With the `segments` subcommand, split the whole
+/// input (or the file given) into packet and message segments and print a segment table.
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let windows: Vec<usize> = args
+        .windows(2)
+        .filter(|pair| pair[0] == "--window")
+        .map(|pair| pair[1].parse::<usize>().expect("Unable to parse window size"))
+        .collect();
+
+    match args.first().map(String::as_str) {
+        Some("bench") => {
+            let megabytes = args.get(1).map_or(Ok(8), |arg| arg.parse::<usize>());
+            run_benchmark(megabytes.expect("Unable to parse number of megabytes"));
+            return;
+        }
+        Some("stream") => {
+            let windows =
+                if windows.is_empty() { vec![PACKET_WINDOW, MESSAGE_WINDOW] } else { windows };
+            match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(path) => {
+                    let file = File::open(path).expect("Unable to open signal file");
+                    print_stream_markers(file, &windows);
+                }
+                None => print_stream_markers(stdin().lock(), &windows),
+            }
+            return;
+        }
+        Some("segments") => {
+            let path = args.get(1).map_or("./input_01.txt", String::as_str);
+            let input_string = read_to_string(path).expect("Unable to read transmission file");
+            print_segments(input_string.trim_end().as_bytes());
+            return;
+        }
+        _ => {}
+    }
+
     let path = Path::new("./input_01.txt");
     let input_string = read_to_string(path).expect("Unable to read input file 'input_01.txt'");
-    if let Some((packet_end, message_end)) = identify_markers(input_string) {
-        println!(
-            "The packet marker ends at character {}; the message marker ends at character {}",
-            packet_end, message_end
-        );
-    } else {
-        println!("Packet or message marker missing from message.");
+    let stream = input_string.as_bytes();
+
+    if windows.is_empty() {
+        let names = ["packet", "message"];
+        let windows = [PACKET_WINDOW, MESSAGE_WINDOW];
+        for ((name, window), marker_end) in
+            names.iter().zip(windows).zip(find_markers(stream, &windows))
+        {
+            match marker_end {
+                Some(marker_end) => {
+                    println!("The {} marker ends at byte {}", name, marker_end)
+                }
+                None => {
+                    println!("No {} marker of {} distinct bytes in message.", name, window)
+                }
+            }
+        }
+        return;
+    }
+
+    for (window, marker_end) in windows.iter().zip(find_markers(stream, &windows)) {
+        match marker_end {
+            Some(marker_end) => {
+                println!("The marker of {} distinct bytes ends at byte {}", window, marker_end)
+            }
+            None => println!("No marker of {} distinct bytes in message.", window),
+        }
     }
 }
@@ -0,0 +1,157 @@
+//! Incremental decoding of a signal as it arrives, one chunk at a time.
+use std::io::{self, ErrorKind, Read};
+
+/// The completion of the first marker of a window size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MarkerEvent {
+    pub window: usize,
+    /// The number of bytes read when the marker completed.
+    pub end: usize,
+}
+
+/// A decoder which is fed the signal in chunks and reports each marker as soon as it completes.
+pub struct Decoder {
+    windows: Vec<usize>,
+    found: Vec<bool>,
+    /// The number of bytes read when each byte was last seen.
+    last_seen: [usize; 256],
+    /// Where the longest run of distinct bytes ending at the last byte read starts.
+    run_start: usize,
+    position: usize,
+}
+
+impl Decoder {
+    /// Create a decoder looking for the first marker of each window size.
+    pub fn new(windows: &[usize]) -> Decoder {
+        Decoder {
+            windows: windows.to_vec(),
+            found: vec![false; windows.len()],
+            last_seen: [0; 256],
+            run_start: 0,
+            position: 0,
+        }
+    }
+
+    /// The number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether every marker has been found, so no more of the signal needs to be read.
+    pub fn is_done(&self) -> bool {
+        self.found.iter().all(|found| *found)
+    }
+
+    /// Record the markers completed by the current run of distinct bytes.
+    fn check_windows(&mut self, events: &mut Vec<MarkerEvent>) {
+        let run_length = self.position - self.run_start;
+        for (found, window) in self.found.iter_mut().zip(&self.windows) {
+            if !*found && run_length >= *window {
+                *found = true;
+                events.push(MarkerEvent { window: *window, end: self.position });
+            }
+        }
+    }
+
+    /// Read the next chunk of the signal, returning the markers which it completed in the
+    /// order they completed. Bytes after the last marker is found are ignored.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<MarkerEvent> {
+        let mut events: Vec<MarkerEvent> = Vec::new();
+        self.check_windows(&mut events);
+        for byte in chunk {
+            if self.is_done() {
+                break;
+            }
+            self.run_start = self.run_start.max(self.last_seen[usize::from(*byte)]);
+            self.position += 1;
+            self.last_seen[usize::from(*byte)] = self.position;
+            self.check_windows(&mut events);
+        }
+        events
+    }
+}
+
+/// Feed a signal from a reader to the decoder as it arrives, calling `on_event` for each marker
+/// as soon as it completes. Reading stops once every marker is found, without waiting for the
+/// end of the signal.
+pub fn decode_stream<R: Read, F: FnMut(&MarkerEvent)>(
+    mut reader: R,
+    decoder: &mut Decoder,
+    mut on_event: F,
+) -> io::Result<()> {
+    let mut buffer = [0; 4096];
+    while !decoder.is_done() {
+        let n_bytes = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n_bytes) => n_bytes,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        decoder.feed(&buffer[..n_bytes]).iter().for_each(&mut on_event);
+    }
+    Ok(())
+}
+
+/// Tests for the streaming decoder.
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{find_markers, MESSAGE_WINDOW, PACKET_WINDOW};
+
+    /// A reader giving at most `chunk_size` bytes per read, then failing once the data runs out.
+    struct ChunkedReader<'a> {
+        data: &'a [u8],
+        chunk_size: usize,
+    }
+
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() {
+                return Err(io::Error::other("Read past the end of the data"));
+            }
+            let n_bytes = self.chunk_size.min(buffer.len()).min(self.data.len());
+            buffer[..n_bytes].copy_from_slice(&self.data[..n_bytes]);
+            self.data = &self.data[n_bytes..];
+            Ok(n_bytes)
+        }
+    }
+
+    /// Test that the markers are the same wherever the chunks split the input, and that reading
+    /// stops as soon as they are found.
+    #[test]
+    fn test_chunk_sizes() {
+        let windows = [MESSAGE_WINDOW, PACKET_WINDOW, 0];
+        let examples = [
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+        ];
+        for input in examples.map(str::as_bytes) {
+            let expected = find_markers(input, &windows);
+            let message_end = expected[0].unwrap();
+
+            for chunk_size in 1..=input.len() {
+                let mut decoder = Decoder::new(&windows);
+                let mut events: Vec<MarkerEvent> = Vec::new();
+                for chunk in input.chunks(chunk_size) {
+                    events.extend(decoder.feed(chunk));
+                }
+                assert_eq!(
+                    events,
+                    vec![
+                        MarkerEvent { window: 0, end: 0 },
+                        MarkerEvent { window: PACKET_WINDOW, end: expected[1].unwrap() },
+                        MarkerEvent { window: MESSAGE_WINDOW, end: message_end },
+                    ]
+                );
+                assert_eq!(decoder.position(), message_end);
+
+                let reader = ChunkedReader { data: &input[..message_end], chunk_size };
+                let mut decoder = Decoder::new(&windows);
+                let mut n_events = 0;
+                decode_stream(reader, &mut decoder, |_| n_events += 1).unwrap();
+                assert_eq!(n_events, 3);
+            }
+        }
+    }
+}